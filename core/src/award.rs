@@ -0,0 +1,37 @@
+use game::PlayerColor;
+
+/// Decides who holds a special card like Longest Road or Largest Army given
+/// every player's current score towards it. The card needs at least `minimum`
+/// and only changes hands when the holder is strictly beaten. If the holder
+/// falls behind and several players tie for the lead, nobody holds it.
+pub fn award_holder(
+    scores: &[(PlayerColor, u32)],
+    holder: Option<PlayerColor>,
+    minimum: u32,
+) -> Option<PlayerColor> {
+    let best = scores.iter().map(|&(_, score)| score).max().unwrap_or(0);
+    if best < minimum {
+        return None;
+    }
+
+    let holder_score = holder.and_then(|holder| {
+        scores
+            .iter()
+            .find(|&&(color, _)| color == holder)
+            .map(|&(_, score)| score)
+    });
+    if holder_score == Some(best) {
+        return holder;
+    }
+
+    let leaders: Vec<PlayerColor> = scores
+        .iter()
+        .filter(|&&(_, score)| score == best)
+        .map(|&(color, _)| color)
+        .collect();
+    if leaders.len() == 1 {
+        Some(leaders[0])
+    } else {
+        None
+    }
+}
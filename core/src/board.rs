@@ -1,11 +1,9 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use common::GameResource;
+use common::{GameResource, pairs, sorted};
 use super::game::{ResourceType, PlayerColor};
-use rand;
-use rand::Rng;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct InternalCoord {
     pub x: i32,
     pub y: i32,
@@ -38,12 +36,14 @@ impl InternalCoord {
     }
 
     pub fn adjacent(&self, other: &InternalCoord) -> bool {
-        self.distance(other) == 2.0
+        self.distance(other) == 1
     }
 
-    pub fn distance(&self, other: &InternalCoord) -> f32 {
-        ((self.x as f32 - other.x as f32).abs() + (self.y as f32 - other.y as f32).abs() +
-             (self.z as f32 - other.z as f32).abs())
+    /// The number of steps between the two points on the lattice.
+    pub fn distance(&self, other: &InternalCoord) -> u32 {
+        let (dx, dy, dz) = (self.x - other.x, self.y - other.y, self.z - other.z);
+
+        dx.abs().max(dy.abs()).max(dz.abs()) as u32
     }
 
     pub fn neighbors(&self) -> Vec<InternalCoord> {
@@ -70,32 +70,24 @@ impl InternalCoord {
         diagonals
     }
 
-    // pub fn adjacent_facing_tiles(
-    //     a: &InternalCoord,
-    //     b: &InternalCoord,
-    // ) -> (InternalCoord, InternalCoord) {
-    //     assert!(a.adjacent(b), "a and b must be adjacent!");
-
-    //     let a_neighbors: HashSet<_> = a.neighbors().into_iter().collect();
-    //     let b_neighbors: HashSet<_> = b.neighbors().into_iter().collect();
-
-    //     let common_neighbors: Vec<_> = a_neighbors.intersection(&b_neighbors).take(2).collect();
-    //     assert!(
-    //         common_neighbors.len() == 2,
-    //         "Incorrect number of common neighbors!"
-    //     );
-
-    //     (
-    //         *common_neighbors
-    //             .get(0)
-    //             .expect("Failed to get first common neighbor")
-    //             .clone(),
-    //         *common_neighbors
-    //             .get(1)
-    //             .expect("Failed to get second common neighbor")
-    //             .clone(),
-    //     )
-    // }
+    /// The two points adjacent to both `a` and `b`, in sorted order. For
+    /// the ends of a road these are the hexes on either side of it.
+    pub fn adjacent_facing_tiles(
+        a: &InternalCoord,
+        b: &InternalCoord,
+    ) -> (InternalCoord, InternalCoord) {
+        assert!(a.adjacent(b), "a and b must be adjacent!");
+
+        let b_neighbors: HashSet<_> = b.neighbors().into_iter().collect();
+        let mut common_neighbors: Vec<_> = a.neighbors()
+            .into_iter()
+            .filter(|neighbor| b_neighbors.contains(neighbor))
+            .collect();
+        common_neighbors.sort();
+        assert_eq!(common_neighbors.len(), 2, "Incorrect number of common neighbors!");
+
+        (common_neighbors[0], common_neighbors[1])
+    }
 }
 
 impl fmt::Debug for InternalCoord {
@@ -110,19 +102,63 @@ impl fmt::Display for InternalCoord {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// A road position between two adjacent building tiles. The endpoints are
+/// stored in sorted order so that each edge has exactly one representation.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct InternalEdge {
+    a: InternalCoord,
+    b: InternalCoord,
+}
+
+impl InternalEdge {
+    pub fn new(a: InternalCoord, b: InternalCoord) -> InternalEdge {
+        assert!(a.adjacent(&b), "Edge endpoints must be adjacent!");
+
+        if a < b {
+            InternalEdge { a, b }
+        } else {
+            InternalEdge { a: b, b: a }
+        }
+    }
+
+    pub fn endpoints(&self) -> (InternalCoord, InternalCoord) {
+        (self.a, self.b)
+    }
+
+    pub fn contains(&self, coordinate: InternalCoord) -> bool {
+        self.a == coordinate || self.b == coordinate
+    }
+
+    pub fn other_endpoint(&self, coordinate: InternalCoord) -> Option<InternalCoord> {
+        if self.a == coordinate {
+            Some(self.b)
+        } else if self.b == coordinate {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for InternalEdge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} - {}", self.a, self.b)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum InternalTileType {
     BuildingTile(BuildingTileContainer),
     ResourceTile(ResourceTileType),
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct BuildingTileContainer {
     pub building: Option<(PlayerColor, BuildingType)>,
     pub harbor_type: Option<HarborType>,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum HarborType {
     All,
     Ore,
@@ -167,7 +203,7 @@ impl GameResource for HarborType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum BuildingType {
     Settlement,
     City,
@@ -194,7 +230,7 @@ impl GameResource for BuildingType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ResourceTileType {
     Mountains,
     Hills,
@@ -243,7 +279,7 @@ impl GameResource for ResourceTileType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RollToken {
     Two,
     Three,
@@ -295,11 +331,13 @@ impl RollToken {
     }
 
     pub fn high_prob(&self) -> bool {
-        match self {
-            &RollToken::Six |
-            &RollToken::Eight => true,
-            _ => false,
-        }
+        matches!(*self, RollToken::Six | RollToken::Eight)
+    }
+
+    /// The number of dice combinations that roll this value, as shown by the
+    /// dots on the token.
+    pub fn pips(self) -> u32 {
+        6 - (7 - self.value() as i32).unsigned_abs()
     }
 }
 
@@ -311,18 +349,18 @@ impl fmt::Display for RollToken {
 
 impl PartialEq<u32> for RollToken {
     fn eq(&self, other: &u32) -> bool {
-        match self {
-            &RollToken::Two => *other == 2,
-            &RollToken::Three => *other == 3,
-            &RollToken::Four => *other == 4,
-            &RollToken::Five => *other == 5,
-            &RollToken::Six => *other == 6,
-            &RollToken::Seven => *other == 7,
-            &RollToken::Eight => *other == 8,
-            &RollToken::Nine => *other == 9,
-            &RollToken::Ten => *other == 10,
-            &RollToken::Eleven => *other == 11,
-            &RollToken::Twelve => *other == 12,
+        match *self {
+            RollToken::Two => *other == 2,
+            RollToken::Three => *other == 3,
+            RollToken::Four => *other == 4,
+            RollToken::Five => *other == 5,
+            RollToken::Six => *other == 6,
+            RollToken::Seven => *other == 7,
+            RollToken::Eight => *other == 8,
+            RollToken::Nine => *other == 9,
+            RollToken::Ten => *other == 10,
+            RollToken::Eleven => *other == 11,
+            RollToken::Twelve => *other == 12,
         }
     }
 }
@@ -363,7 +401,7 @@ impl GameResource for RollToken {
     }
 }
 
-const STANDARD_TILE_LOCATIONS: [InternalCoord; 19] = [
+pub const STANDARD_TILE_LOCATIONS: [InternalCoord; 19] = [
     InternalCoord { x: 0, y: 0, z: 0 }, // Center
     InternalCoord { x: 2, y: -1, z: -1 }, // Start first ring
     InternalCoord { x: 1, y: 1, z: -2 },
@@ -408,91 +446,287 @@ const BALANCED_TILE_VALUES: [(ResourceTileType, Option<RollToken>); 19] =
         (ResourceTileType::Mountains, Some(RollToken::Eight)),
     ];
 
-const BALANCED_HARBOR_LOCATIONS: [(InternalCoord, (HarborType, u32)); 9] =
+/// The extension board keeps every standard hex and widens it with a column
+/// on one side, giving rows of 3, 4, 5, 6, 5, 4 and 3 hexes.
+pub const EXTENDED_TILE_LOCATIONS: [InternalCoord; 30] = [
+    InternalCoord { x: 0, y: 0, z: 0 }, // Center
+    InternalCoord { x: 2, y: -1, z: -1 }, // Start first ring
+    InternalCoord { x: 1, y: 1, z: -2 },
+    InternalCoord { x: -1, y: 2, z: -1 },
+    InternalCoord { x: -2, y: 1, z: 1 },
+    InternalCoord { x: -1, y: -1, z: 2 },
+    InternalCoord { x: 1, y: -2, z: 1 }, // End first ring
+    InternalCoord { x: 3, y: -3, z: 0 }, // Start second ring
+    InternalCoord { x: 4, y: -2, z: -2 },
+    InternalCoord { x: 3, y: 0, z: -3 },
+    InternalCoord { x: 2, y: 2, z: -4 },
+    InternalCoord { x: 0, y: 3, z: -3 },
+    InternalCoord { x: -2, y: 4, z: -2 },
+    InternalCoord { x: -3, y: 3, z: 0 },
+    InternalCoord { x: -4, y: 2, z: 2 },
+    InternalCoord { x: -3, y: 0, z: 3 },
+    InternalCoord { x: -2, y: -2, z: 4 },
+    InternalCoord { x: 0, y: -3, z: 3 },
+    InternalCoord { x: 2, y: -4, z: 2 },
+    InternalCoord { x: -6, y: 3, z: 3 }, // Start of the extension hexes
+    InternalCoord { x: -5, y: 4, z: 1 },
+    InternalCoord { x: -4, y: 5, z: -1 },
+    InternalCoord { x: -3, y: 6, z: -3 },
+    InternalCoord { x: -1, y: 5, z: -4 },
+    InternalCoord { x: 1, y: 4, z: -5 },
+    InternalCoord { x: 1, y: -5, z: 4 },
+    InternalCoord { x: -1, y: -4, z: 5 },
+    InternalCoord { x: -3, y: -3, z: 6 },
+    InternalCoord { x: -4, y: -1, z: 5 },
+    InternalCoord { x: -5, y: 1, z: 4 },
+];
+
+const EXTENDED_BALANCED_TILE_VALUES: [(ResourceTileType, Option<RollToken>); 30] =
+    [
+        (ResourceTileType::Desert, None),
+        (ResourceTileType::Pasture, Some(RollToken::Two)),
+        (ResourceTileType::Fields, Some(RollToken::Eleven)),
+        (ResourceTileType::Pasture, Some(RollToken::Eight)),
+        (ResourceTileType::Forest, Some(RollToken::Ten)),
+        (ResourceTileType::Fields, Some(RollToken::Four)),
+        (ResourceTileType::Pasture, Some(RollToken::Two)),
+        (ResourceTileType::Desert, None),
+        (ResourceTileType::Forest, Some(RollToken::Four)),
+        (ResourceTileType::Mountains, Some(RollToken::Six)),
+        (ResourceTileType::Hills, Some(RollToken::Eleven)),
+        (ResourceTileType::Mountains, Some(RollToken::Nine)),
+        (ResourceTileType::Fields, Some(RollToken::Ten)),
+        (ResourceTileType::Hills, Some(RollToken::Five)),
+        (ResourceTileType::Mountains, Some(RollToken::Twelve)),
+        (ResourceTileType::Mountains, Some(RollToken::Three)),
+        (ResourceTileType::Forest, Some(RollToken::Three)),
+        (ResourceTileType::Hills, Some(RollToken::Eight)),
+        (ResourceTileType::Fields, Some(RollToken::Ten)),
+        (ResourceTileType::Forest, Some(RollToken::Five)),
+        (ResourceTileType::Fields, Some(RollToken::Four)),
+        (ResourceTileType::Pasture, Some(RollToken::Three)),
+        (ResourceTileType::Mountains, Some(RollToken::Six)),
+        (ResourceTileType::Hills, Some(RollToken::Nine)),
+        (ResourceTileType::Forest, Some(RollToken::Six)),
+        (ResourceTileType::Fields, Some(RollToken::Twelve)),
+        (ResourceTileType::Forest, Some(RollToken::Eleven)),
+        (ResourceTileType::Pasture, Some(RollToken::Eight)),
+        (ResourceTileType::Hills, Some(RollToken::Five)),
+        (ResourceTileType::Pasture, Some(RollToken::Nine)),
+    ];
+
+/// Harbors sit on sea hexes. The second value is the direction the harbor
+/// faces, counted in sixths of a turn.
+const STANDARD_HARBOR_LOCATIONS: [(InternalCoord, u32); 9] =
+    [
+        (InternalCoord { x: 6, y: -3, z: -3 }, 1),
+        (InternalCoord { x: 4, y: 1, z: -5 }, 2),
+        (InternalCoord { x: 1, y: 4, z: -5 }, 2),
+        (InternalCoord { x: -3, y: 6, z: -3 }, 3),
+        (InternalCoord { x: -5, y: 4, z: 1 }, 4),
+        (InternalCoord { x: -5, y: 1, z: 4 }, 4),
+        (InternalCoord { x: -3, y: -3, z: 6 }, 5),
+        (InternalCoord { x: 1, y: -5, z: 4 }, 0),
+        (InternalCoord { x: 4, y: -5, z: 1 }, 0),
+    ];
+
+const BALANCED_HARBOR_TYPES: [HarborType; 9] =
     [
-        (InternalCoord { x: 6, y: -3, z: -3 }, (HarborType::All, 1)), //
-        (InternalCoord { x: 4, y: 1, z: -5 }, (HarborType::Brick, 2)), //
-        (InternalCoord { x: 1, y: 4, z: -5 }, (HarborType::Lumber, 2)), //
-        (InternalCoord { x: -3, y: 6, z: -3 }, (HarborType::All, 3)), //
-        (InternalCoord { x: -5, y: 4, z: 1 }, (HarborType::Grain, 4)),
-        (InternalCoord { x: -5, y: 1, z: 4 }, (HarborType::Ore, 4)),
-        (InternalCoord { x: -3, y: -3, z: 6 }, (HarborType::All, 5)),
-        (InternalCoord { x: 1, y: -5, z: 4 }, (HarborType::Wool, 0)), //
-        (InternalCoord { x: 4, y: -5, z: 1 }, (HarborType::All, 0)),
+        HarborType::All,
+        HarborType::Brick,
+        HarborType::Lumber,
+        HarborType::All,
+        HarborType::Grain,
+        HarborType::Ore,
+        HarborType::All,
+        HarborType::Wool,
+        HarborType::All,
     ];
 
-const BALANCED_HARBOR_BUILDING_LOCATIONS: [(InternalCoord, InternalCoord); 9] =
+const EXTENDED_HARBOR_LOCATIONS: [(InternalCoord, u32); 11] =
     [
-        (
-            InternalCoord { x: 5, y: -3, z: -2 },
-            InternalCoord { x: 5, y: -2, z: -3 },
-        ),
-        (
-            InternalCoord { x: 4, y: 0, z: -4 },
-            InternalCoord { x: 3, y: 1, z: -4 },
-        ),
-        (
-            InternalCoord { x: 1, y: 3, z: -4 },
-            InternalCoord { x: 0, y: 4, z: -4 },
-        ),
-        (
-            InternalCoord { x: -2, y: 5, z: -3 },
-            InternalCoord { x: -3, y: 5, z: -2 },
-        ),
-        (
-            InternalCoord { x: -4, y: 4, z: 0 },
-            InternalCoord { x: -4, y: 3, z: 1 },
-        ),
-        (
-            InternalCoord { x: -4, y: 1, z: 3 },
-            InternalCoord { x: -4, y: 0, z: 4 },
-        ),
-        (
-            InternalCoord { x: -3, y: -2, z: 5 },
-            InternalCoord { x: -2, y: -3, z: 5 },
-        ),
-        (
-            InternalCoord { x: 0, y: -4, z: 4 },
-            InternalCoord { x: 1, y: -4, z: 3 },
-        ),
-        (
-            InternalCoord { x: 3, y: -4, z: 1 },
-            InternalCoord { x: 4, y: -4, z: 0 },
-        ),
+        (InternalCoord { x: 6, y: -3, z: -3 }, 1),
+        (InternalCoord { x: 4, y: 1, z: -5 }, 2),
+        (InternalCoord { x: 2, y: 5, z: -7 }, 2),
+        (InternalCoord { x: -2, y: 7, z: -5 }, 3),
+        (InternalCoord { x: -5, y: 7, z: -2 }, 3),
+        (InternalCoord { x: -7, y: 5, z: 2 }, 4),
+        (InternalCoord { x: -7, y: 2, z: 5 }, 4),
+        (InternalCoord { x: -5, y: -2, z: 7 }, 5),
+        (InternalCoord { x: -2, y: -5, z: 7 }, 5),
+        (InternalCoord { x: 2, y: -7, z: 5 }, 0),
+        (InternalCoord { x: 4, y: -5, z: 1 }, 0),
     ];
 
-#[derive(Debug)]
+const EXTENDED_BALANCED_HARBOR_TYPES: [HarborType; 11] =
+    [
+        HarborType::All,
+        HarborType::Brick,
+        HarborType::Lumber,
+        HarborType::All,
+        HarborType::Wool,
+        HarborType::Grain,
+        HarborType::Ore,
+        HarborType::All,
+        HarborType::Wool,
+        HarborType::All,
+        HarborType::All,
+    ];
+
+/// The two building tiles served by the harbor at `coordinate` facing `axis`.
+pub fn harbor_building_locations(coordinate: InternalCoord, axis: u32) -> Vec<InternalCoord> {
+    vec![(axis + 2) % 6, (axis + 3) % 6]
+        .into_iter()
+        .map(|direction| {
+            let (dx, dy, dz) = TILE_COORD_DIR[direction as usize];
+            InternalCoord::new(coordinate.x + dx, coordinate.y + dy, coordinate.z + dz)
+        })
+        .collect()
+}
+
+/// The 19 hex board for up to four players, or the 30 hex board from the
+/// 5-6 player extension.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum BoardShape {
+    Standard,
+    Extended,
+}
+
+impl BoardShape {
+    pub fn for_player_count(count: usize) -> BoardShape {
+        if count > 4 {
+            BoardShape::Extended
+        } else {
+            BoardShape::Standard
+        }
+    }
+
+    /// Hex positions from the center outwards.
+    pub fn tile_locations(self) -> &'static [InternalCoord] {
+        match self {
+            BoardShape::Standard => &STANDARD_TILE_LOCATIONS,
+            BoardShape::Extended => &EXTENDED_TILE_LOCATIONS,
+        }
+    }
+
+    pub fn harbor_locations(self) -> &'static [(InternalCoord, u32)] {
+        match self {
+            BoardShape::Standard => &STANDARD_HARBOR_LOCATIONS,
+            BoardShape::Extended => &EXTENDED_HARBOR_LOCATIONS,
+        }
+    }
+
+    pub fn tile_count(self, tile_type: ResourceTileType) -> usize {
+        match (self, tile_type) {
+            (BoardShape::Standard, _) => tile_type.count(),
+            (BoardShape::Extended, ResourceTileType::Mountains) |
+            (BoardShape::Extended, ResourceTileType::Hills) => 5,
+            (BoardShape::Extended, ResourceTileType::Desert) => 2,
+            (BoardShape::Extended, _) => 6,
+        }
+    }
+
+    pub fn token_count(self, roll_token: RollToken) -> usize {
+        match (self, roll_token) {
+            (BoardShape::Standard, _) => roll_token.count(),
+            (BoardShape::Extended, RollToken::Seven) => 0,
+            (BoardShape::Extended, RollToken::Two) |
+            (BoardShape::Extended, RollToken::Twelve) => 2,
+            (BoardShape::Extended, _) => 3,
+        }
+    }
+
+    pub fn harbor_count(self, harbor_type: HarborType) -> usize {
+        match (self, harbor_type) {
+            (BoardShape::Standard, _) => harbor_type.count(),
+            (BoardShape::Extended, HarborType::All) => 5,
+            (BoardShape::Extended, HarborType::Wool) => 2,
+            (BoardShape::Extended, _) => 1,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Board {
+    #[serde(with = "pairs")]
     pub tiles: HashMap<InternalCoord, InternalTileType>,
+    #[serde(with = "pairs")]
     pub roll_tokens: HashMap<InternalCoord, RollToken>,
+    #[serde(with = "pairs")]
     pub harbors: HashMap<InternalCoord, (HarborType, u32)>,
+    #[serde(with = "pairs")]
+    pub roads: HashMap<InternalEdge, PlayerColor>,
+    pub robber: Option<InternalCoord>,
+    /// Sea hexes. They hold nothing but harbors.
+    #[serde(with = "sorted")]
+    pub water: HashSet<InternalCoord>,
 }
 
-impl Default for Board {
-    fn default() -> Board {
-        Board {
-            tiles: HashMap::default(),
-            roll_tokens: HashMap::default(),
-            harbors: HashMap::default(),
+impl Board {
+    pub fn balanced_start() -> Board {
+        Board::balanced(BoardShape::Standard)
+    }
+
+    pub fn balanced(shape: BoardShape) -> Board {
+        match shape {
+            BoardShape::Standard => {
+                Board::with_layout(shape, &BALANCED_TILE_VALUES, &BALANCED_HARBOR_TYPES)
+            }
+            BoardShape::Extended => {
+                Board::with_layout(
+                    shape,
+                    &EXTENDED_BALANCED_TILE_VALUES,
+                    &EXTENDED_BALANCED_HARBOR_TYPES,
+                )
+            }
         }
     }
-}
 
-impl Board {
-    pub fn balanced_start() -> Board {
+    /// Builds a board of the given shape. `tiles` is listed in the order of
+    /// `shape.tile_locations()` and `harbors` fills its harbor positions.
+    pub fn with_layout(
+        shape: BoardShape,
+        tiles: &[(ResourceTileType, Option<RollToken>)],
+        harbors: &[HarborType],
+    ) -> Board {
+        let tile_locations = shape.tile_locations();
+        let harbor_locations = shape.harbor_locations();
+        assert_eq!(tiles.len(), tile_locations.len(), "Wrong number of tiles!");
+        assert_eq!(harbors.len(), harbor_locations.len(), "Wrong number of harbors!");
+
+        let tiles: Vec<(InternalCoord, ResourceTileType, Option<RollToken>)> = tile_locations
+            .iter()
+            .zip(tiles.iter())
+            .map(|(&coordinate, &(tile_type, roll_token))| (coordinate, tile_type, roll_token))
+            .collect();
+        let harbors: Vec<(InternalCoord, HarborType, u32)> = harbor_locations
+            .iter()
+            .zip(harbors.iter())
+            .map(|(&(coordinate, axis), &harbor_type)| (coordinate, harbor_type, axis))
+            .collect();
+
+        Board::from_tiles(&tiles, &harbors)
+    }
+
+    /// Builds a board from hexes at arbitrary positions. Harbors are given as
+    /// a sea hex, its type and the direction it faces. The sea around the
+    /// land becomes water and the robber starts on the first desert.
+    pub fn from_tiles(
+        tiles: &[(InternalCoord, ResourceTileType, Option<RollToken>)],
+        harbors: &[(InternalCoord, HarborType, u32)],
+    ) -> Board {
         let mut board = Board::default();
 
-        let harbor_buildings: HashMap<InternalCoord, usize> = BALANCED_HARBOR_BUILDING_LOCATIONS
+        let harbor_buildings: HashMap<InternalCoord, HarborType> = harbors
             .iter()
-            .cloned()
-            .enumerate()
-            .flat_map(|(index, (coord_a, coord_b))| {
-                vec![(coord_a, index), (coord_b, index)].into_iter()
+            .flat_map(|&(coordinate, harbor_type, axis)| {
+                harbor_building_locations(coordinate, axis)
+                    .into_iter()
+                    .map(move |building| (building, harbor_type))
             })
             .collect();
 
-        for (index, &coordinate) in STANDARD_TILE_LOCATIONS.iter().enumerate() {
-            let &(tile_type, roll_token) = &BALANCED_TILE_VALUES[index];
+        for &(coordinate, tile_type, roll_token) in tiles.iter() {
             board.tiles.insert(
                 coordinate,
                 InternalTileType::ResourceTile(tile_type),
@@ -501,34 +735,30 @@ impl Board {
             if let Some(roll_token) = roll_token {
                 board.roll_tokens.insert(coordinate, roll_token);
             }
+            if tile_type == ResourceTileType::Desert && board.robber.is_none() {
+                board.robber = Some(coordinate);
+            }
 
             for neighbor in coordinate.neighbors() {
-                if !board.tiles.contains_key(&neighbor) {
-
-                    let building_tile =
-                        if let Some(harbor_index) = harbor_buildings.get(&neighbor) {
-                            BuildingTileContainer {
-                                building: None,
-                                harbor_type: Some((BALANCED_HARBOR_LOCATIONS[*harbor_index].1).0),
-                            }
-                        } else {
-                            BuildingTileContainer {
-                                building: None,
-                                harbor_type: None,
-                            }
-                        };
-
-                    board.tiles.insert(
-                        neighbor,
-                        InternalTileType::BuildingTile(building_tile),
-                    );
-                }
+                board.tiles.entry(neighbor).or_insert_with(|| {
+                    let building_tile = BuildingTileContainer {
+                        building: None,
+                        harbor_type: harbor_buildings.get(&neighbor).cloned(),
+                    };
+
+                    InternalTileType::BuildingTile(building_tile)
+                });
             }
         }
 
-        board.harbors.extend(
-            BALANCED_HARBOR_LOCATIONS.iter().cloned()
-        );
+        board.water = tiles
+            .iter()
+            .flat_map(|&(coordinate, _, _)| coordinate.diagonal_neighbors().into_iter())
+            .filter(|coordinate| !board.tiles.contains_key(coordinate))
+            .collect();
+        board.harbors.extend(harbors.iter().map(|&(coordinate, harbor_type, axis)| {
+            (coordinate, (harbor_type, axis))
+        }));
 
         board
     }
@@ -544,4 +774,228 @@ impl Board {
 
         (tile_type, roll_token)
     }
+
+    pub fn building_tile(&self, coordinate: InternalCoord) -> Option<&BuildingTileContainer> {
+        match self.tiles.get(&coordinate) {
+            Some(InternalTileType::BuildingTile(building_tile)) => Some(building_tile),
+            _ => None,
+        }
+    }
+
+    pub fn building_at(&self, coordinate: InternalCoord) -> Option<(PlayerColor, BuildingType)> {
+        self.building_tile(coordinate).and_then(
+            |building_tile| building_tile.building,
+        )
+    }
+
+    pub fn place_building(
+        &mut self,
+        coordinate: InternalCoord,
+        color: PlayerColor,
+        building: BuildingType,
+    ) {
+        match self.tiles.get_mut(&coordinate) {
+            Some(InternalTileType::BuildingTile(building_tile)) => {
+                building_tile.building = Some((color, building));
+            }
+            _ => panic!("Cannot place a building on a non-building tile!"),
+        }
+    }
+
+    pub fn building_tiles(&self) -> Vec<InternalCoord> {
+        let mut coordinates: Vec<InternalCoord> = self.tiles
+            .iter()
+            .filter(|&(_, tile)| matches!(*tile, InternalTileType::BuildingTile(_)))
+            .map(|(&coordinate, _)| coordinate)
+            .collect();
+        coordinates.sort();
+
+        coordinates
+    }
+
+    pub fn resource_tiles(&self) -> Vec<InternalCoord> {
+        let mut coordinates: Vec<InternalCoord> = self.tiles
+            .iter()
+            .filter(|&(_, tile)| matches!(*tile, InternalTileType::ResourceTile(_)))
+            .map(|(&coordinate, _)| coordinate)
+            .collect();
+        coordinates.sort();
+
+        coordinates
+    }
+
+    pub fn is_blocked(&self, coordinate: InternalCoord) -> bool {
+        self.robber == Some(coordinate)
+    }
+
+    pub fn adjacent_building_tiles(&self, coordinate: InternalCoord) -> Vec<InternalCoord> {
+        coordinate
+            .neighbors()
+            .into_iter()
+            .filter(|neighbor| self.building_tile(*neighbor).is_some())
+            .collect()
+    }
+
+    pub fn adjacent_resource_tiles(&self, coordinate: InternalCoord) -> Vec<InternalCoord> {
+        coordinate
+            .neighbors()
+            .into_iter()
+            .filter(|neighbor| {
+                matches!(self.tiles.get(neighbor), Some(&InternalTileType::ResourceTile(_)))
+            })
+            .collect()
+    }
+
+    pub fn buildings_of(&self, color: PlayerColor, building: BuildingType) -> Vec<InternalCoord> {
+        self.tiles
+            .iter()
+            .filter_map(|(&coordinate, tile)| match *tile {
+                InternalTileType::BuildingTile(BuildingTileContainer {
+                    building: Some(placed), ..
+                }) if placed == (color, building) => Some(coordinate),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn is_edge(&self, edge: InternalEdge) -> bool {
+        let (a, b) = edge.endpoints();
+
+        self.building_tile(a).is_some() && self.building_tile(b).is_some()
+    }
+
+    pub fn edges(&self) -> Vec<InternalEdge> {
+        let mut edges: Vec<InternalEdge> = self.building_tiles()
+            .into_iter()
+            .flat_map(|coordinate| self.edges_at(coordinate).into_iter())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        edges.sort();
+
+        edges
+    }
+
+    pub fn edges_at(&self, coordinate: InternalCoord) -> Vec<InternalEdge> {
+        self.adjacent_building_tiles(coordinate)
+            .into_iter()
+            .map(|neighbor| InternalEdge::new(coordinate, neighbor))
+            .collect()
+    }
+
+    pub fn road_at(&self, edge: InternalEdge) -> Option<PlayerColor> {
+        self.roads.get(&edge).cloned()
+    }
+
+    pub fn place_road(&mut self, edge: InternalEdge, color: PlayerColor) {
+        assert!(self.is_edge(edge), "Cannot place a road off the board!");
+
+        self.roads.insert(edge, color);
+    }
+
+    pub fn roads_of(&self, color: PlayerColor) -> Vec<InternalEdge> {
+        self.roads
+            .iter()
+            .filter(|&(_, owner)| *owner == color)
+            .map(|(&edge, _)| edge)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+    use board::{Board, BoardShape, InternalCoord, InternalEdge, InternalTileType};
+    use game::PlayerColor;
+
+    #[test]
+    fn test_balanced_board_edges() {
+        let board = Board::balanced_start();
+
+        assert_eq!(board.building_tiles().len(), 54);
+        assert_eq!(board.resource_tiles().len(), 19);
+        assert_eq!(board.robber, Some(InternalCoord::new(0, 0, 0)));
+        assert_eq!(board.edges().len(), 72);
+        assert!(board.edges().iter().all(|edge| board.is_edge(*edge)));
+    }
+
+    #[test]
+    fn test_extended_board() {
+        let board = Board::balanced(BoardShape::Extended);
+
+        assert_eq!(board.building_tiles().len(), 80);
+        assert_eq!(board.resource_tiles().len(), 30);
+        assert_eq!(board.roll_tokens.len(), 28);
+        assert_eq!(board.edges().len(), 109);
+        assert_eq!(board.robber, Some(InternalCoord::new(0, 0, 0)));
+
+        // Every harbor serves two coastal building tiles of its own
+        let harbor_buildings = board
+            .tiles
+            .values()
+            .filter(|tile| match **tile {
+                InternalTileType::BuildingTile(building) => building.harbor_type.is_some(),
+                _ => false,
+            })
+            .count();
+        assert_eq!(board.harbors.len(), 11);
+        assert_eq!(harbor_buildings, 22);
+    }
+
+    #[test]
+    fn test_edge_is_canonical() {
+        let a = InternalCoord::new(1, -1, 0);
+        let b = InternalCoord::new(1, 0, -1);
+
+        assert_eq!(InternalEdge::new(a, b), InternalEdge::new(b, a));
+        assert_eq!(InternalEdge::new(a, b).other_endpoint(a), Some(b));
+        assert_eq!(
+            InternalEdge::new(a, b).other_endpoint(InternalCoord::new(0, 0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_edge_requires_adjacency() {
+        InternalEdge::new(InternalCoord::new(1, -1, 0), InternalCoord::new(-1, 1, 0));
+    }
+
+    #[test]
+    fn test_adjacent_facing_tiles() {
+        let a = InternalCoord::new(1, -1, 0);
+        let b = InternalCoord::new(1, 0, -1);
+
+        assert_eq!(a.distance(&b), 1);
+        assert_eq!(a.distance(&InternalCoord::new(-1, 1, 0)), 2);
+        assert_eq!(
+            InternalCoord::adjacent_facing_tiles(&a, &b),
+            (InternalCoord::new(0, 0, 0), InternalCoord::new(2, -1, -1))
+        );
+        assert_eq!(
+            InternalCoord::adjacent_facing_tiles(&b, &a),
+            InternalCoord::adjacent_facing_tiles(&a, &b)
+        );
+    }
+
+    #[test]
+    fn test_edges_at() {
+        let board = Board::balanced_start();
+
+        // Interior vertices have three edges, coastal ones may only have two
+        assert_eq!(board.edges_at(InternalCoord::new(1, -1, 0)).len(), 3);
+        assert_eq!(board.edges_at(InternalCoord::new(4, -3, -1)).len(), 3);
+        assert_eq!(board.edges_at(InternalCoord::new(5, -3, -2)).len(), 2);
+    }
+
+    #[test]
+    fn test_road_ownership() {
+        let mut board = Board::balanced_start();
+        let edge = InternalEdge::new(InternalCoord::new(1, -1, 0), InternalCoord::new(1, 0, -1));
+
+        assert_eq!(board.road_at(edge), None);
+        board.place_road(edge, PlayerColor::Red);
+        assert_eq!(board.road_at(edge), Some(PlayerColor::Red));
+        assert_eq!(board.roads_of(PlayerColor::Red), vec![edge]);
+        assert!(board.roads_of(PlayerColor::Blue).is_empty());
+    }
 }
@@ -1,20 +1,88 @@
 use std::iter;
-use rand;
 use rand::Rng;
 use std::collections::HashSet;
 use std::hash::Hash;
 
-pub trait GameResource: Sized + Clone + Copy + Eq + Hash {
-    fn count(self: Self) -> usize;
+pub trait GameResource: Sized + Clone + Copy + Eq + Hash + Ord {
+    fn count(self) -> usize;
     fn all_variants() -> HashSet<Self>;
 
-    fn full_shuffled_collection() -> Vec<Self> {
+    fn full_shuffled_collection<R: Rng>(rng: &mut R) -> Vec<Self> {
+        Self::shuffled_collection(rng, Self::count)
+    }
+
+    /// Like `full_shuffled_collection`, but with `count` giving how many of
+    /// each variant to include.
+    fn shuffled_collection<R: Rng, F: Fn(Self) -> usize>(rng: &mut R, count: F) -> Vec<Self> {
         let mut full_deck = Self::all_variants()
             .into_iter()
-            .flat_map(|variant| iter::repeat(variant).take(variant.count()))
+            .flat_map(|variant| iter::repeat_n(variant, count(variant)))
             .collect::<Vec<Self>>();
-        rand::thread_rng().shuffle(&mut full_deck);
+        // Set iteration order differs between runs, so sort before shuffling
+        // to keep seeded shuffles reproducible.
+        full_deck.sort();
+        rng.shuffle(&mut full_deck);
 
         full_deck
     }
 }
+
+/// Serializes a map as a list of key-value pairs sorted by key. JSON objects
+/// can only have string keys, and sorting keeps the output stable.
+pub mod pairs {
+    use std::collections::HashMap;
+    use std::hash::Hash;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize + Ord,
+        V: Serialize,
+        S: Serializer,
+    {
+        let mut pairs: Vec<(&K, &V)> = map.iter().collect();
+        pairs.sort_by(|a, b| a.0.cmp(b.0));
+
+        serializer.collect_seq(pairs)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs: Vec<(K, V)> = Vec::deserialize(deserializer)?;
+
+        Ok(pairs.into_iter().collect())
+    }
+}
+
+/// Serializes a set as a sorted list, so the output doesn't change from run
+/// to run.
+pub mod sorted {
+    use std::collections::HashSet;
+    use std::hash::Hash;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T, S>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + Ord,
+        S: Serializer,
+    {
+        let mut items: Vec<&T> = set.iter().collect();
+        items.sort();
+
+        serializer.collect_seq(items)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<HashSet<T>, D::Error>
+    where
+        T: Deserialize<'de> + Eq + Hash,
+        D: Deserializer<'de>,
+    {
+        let items: Vec<T> = Vec::deserialize(deserializer)?;
+
+        Ok(items.into_iter().collect())
+    }
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use serde_json;
+use board::{BuildingType, InternalCoord, InternalEdge};
+use game::{DevelopmentCardType, PlayerColor, ResourceCollection};
+use phase::ActionKind;
+use trade::TradeOfferId;
+
+pub type RulesResult<T> = Result<T, RulesError>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RulesError {
+    UnknownPlayer(PlayerColor),
+    NotYourTurn(PlayerColor),
+    GameOver,
+    ActionNotAllowed(ActionKind),
+    InsufficientResources(ResourceShortfall),
+    WrongDiscardAmount(u32),
+    RobberMustMove,
+    InvalidStealTarget(Option<PlayerColor>),
+    InvalidLocation(InternalCoord),
+    LocationOccupied(InternalCoord),
+    InvalidEdge(InternalEdge),
+    EdgeOccupied(InternalEdge),
+    DistanceRuleViolated(InternalCoord),
+    NotConnected,
+    NoPiecesRemaining(BuildingType),
+    DevelopmentDeckEmpty,
+    DevelopmentCardNotPlayable(DevelopmentCardType),
+    DevelopmentCardAlreadyPlayed,
+    SecondRoadRequired,
+    BankExhausted,
+    BankSupplyExceeded,
+    InvalidTrade,
+    UnknownTradeOffer(TradeOfferId),
+    TradeNotAccepted(PlayerColor),
+    NothingToUndo,
+    OutcomeRevealed,
+    NothingToRedo,
+    UnsatisfiableBoardConstraints,
+    /// Holds the `MapError` as text, since it may wrap an I/O error.
+    InvalidMap(String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rules error! ({:?})", self)
+    }
+}
+
+impl Error for RulesError {
+    fn description(&self) -> &str {
+        match *self {
+            RulesError::UnknownPlayer(_) => "Player is not part of this game",
+            RulesError::NotYourTurn(_) => "It is not this player's turn",
+            RulesError::GameOver => "The game is already over",
+            RulesError::ActionNotAllowed(_) => "Action is not allowed in the current phase",
+            RulesError::InsufficientResources(_) => "Player cannot afford this action",
+            RulesError::WrongDiscardAmount(_) => "Player must discard exactly half their hand",
+            RulesError::RobberMustMove => "The robber must move to a different hex",
+            RulesError::InvalidStealTarget(_) => "Player cannot be robbed from this hex",
+            RulesError::InvalidLocation(_) => "Location is not valid for this action",
+            RulesError::LocationOccupied(_) => "Location is already occupied",
+            RulesError::InvalidEdge(_) => "Edge is not valid for this action",
+            RulesError::EdgeOccupied(_) => "Edge already has a road",
+            RulesError::DistanceRuleViolated(_) => "Location is adjacent to another building",
+            RulesError::NotConnected => "Location is not connected to the player's roads",
+            RulesError::NoPiecesRemaining(_) => "Player has no pieces of this type remaining",
+            RulesError::DevelopmentDeckEmpty => "There are no development cards left to buy",
+            RulesError::DevelopmentCardNotPlayable(_) => {
+                "Player has no development card of this type they can play"
+            }
+            RulesError::DevelopmentCardAlreadyPlayed => {
+                "Player has already played a development card this turn"
+            }
+            RulesError::SecondRoadRequired => "Road Building must place both roads if it can",
+            RulesError::BankExhausted => "The bank does not hold enough resources",
+            RulesError::BankSupplyExceeded => "The bank cannot hold more than the game's supply",
+            RulesError::InvalidTrade => "Trade does not match the player's rates",
+            RulesError::UnknownTradeOffer(_) => "Trade offer is not open to this player",
+            RulesError::TradeNotAccepted(_) => "Player has not agreed to this trade",
+            RulesError::NothingToUndo => "There are no actions to take back",
+            RulesError::OutcomeRevealed => {
+                "The last action revealed a random outcome and can't be taken back"
+            }
+            RulesError::NothingToRedo => "There are no undone actions to apply again",
+            RulesError::UnsatisfiableBoardConstraints => {
+                "Could not generate a board that meets the constraints"
+            }
+            RulesError::InvalidMap(_) => "The board's map is not valid",
+        }
+    }
+}
+
+/// The cards missing from a collection that couldn't cover a cost.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ResourceShortfall(pub ResourceCollection);
+
+impl fmt::Display for ResourceShortfall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Resource shortfall! ({:?})", self.0)
+    }
+}
+
+impl Error for ResourceShortfall {
+    fn description(&self) -> &str {
+        "Not enough resources to cover the cost"
+    }
+}
+
+impl From<ResourceShortfall> for RulesError {
+    fn from(src: ResourceShortfall) -> Self {
+        RulesError::InsufficientResources(src)
+    }
+}
+
+pub type MapResult<T> = Result<T, MapError>;
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    Serde(serde_json::Error),
+    InvalidCoordinate(i32, i32, i32),
+    DuplicateHex(InternalCoord),
+    InvalidToken(InternalCoord),
+    InvalidHarbor(InternalCoord),
+    InvalidRobber(InternalCoord),
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Map error! ({:?})", self)
+    }
+}
+
+impl Error for MapError {
+    fn description(&self) -> &str {
+        match *self {
+            MapError::Io(_) => "Could not read or write the map file",
+            MapError::Serde(_) => "Map file is not in the expected format",
+            MapError::InvalidCoordinate(..) => "Position is not the center of a hex",
+            MapError::DuplicateHex(_) => "Hex is listed more than once",
+            MapError::InvalidToken(_) => "Roll token is not valid for this hex",
+            MapError::InvalidHarbor(_) => "Harbor must be on water and face the coast",
+            MapError::InvalidRobber(_) => "Robber must start on a land hex",
+        }
+    }
+}
+
+impl From<io::Error> for MapError {
+    fn from(src: io::Error) -> Self {
+        MapError::Io(src)
+    }
+}
+
+impl From<serde_json::Error> for MapError {
+    fn from(src: serde_json::Error) -> Self {
+        MapError::Serde(src)
+    }
+}
+
+pub type ReplayResult<T> = Result<T, ReplayError>;
+
+/// Why an action log could not be replayed. Steps are counted from zero.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ReplayError {
+    InvalidSettings(RulesError),
+    StepOutOfRange(usize),
+    ActionRejected(usize, RulesError),
+    OutcomeMismatch(usize),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Replay error! ({:?})", self)
+    }
+}
+
+impl Error for ReplayError {
+    fn description(&self) -> &str {
+        match *self {
+            ReplayError::InvalidSettings(_) => "The log's settings can't start a game",
+            ReplayError::StepOutOfRange(_) => "The log does not have that many steps",
+            ReplayError::ActionRejected(..) => "A logged action was rejected when replayed",
+            ReplayError::OutcomeMismatch(_) => {
+                "A logged action had a different random outcome when replayed"
+            }
+        }
+    }
+}
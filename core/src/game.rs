@@ -1,382 +1,2351 @@
-use super::board::{Board, BuildingType};
-use super::common::GameResource;
+use super::board::{Board, BoardShape, BuildingType, InternalCoord, InternalEdge, InternalTileType};
+use super::common::{GameResource, pairs};
+use super::error::{ResourceShortfall, RulesError, RulesResult};
+use super::phase::{ActionKind, GamePhase, SetupRound, SetupStep};
+use super::award::award_holder;
+use super::longest_road::{longest_road, longest_road_holder};
+use super::generator::BoardLayout;
+use super::history::{ActionLog, LogEntry, RandomOutcome};
+use super::rng::GameRng;
+use super::production::{apply_shortage_rule, owed_resources};
+use super::trade::{TradeBook, TradeOffer, TradeOfferId, TradeResponse, is_valid_maritime_trade,
+                   maritime_rates, maritime_value};
 use std::collections::{HashMap, HashSet};
-use std::ops::{Add, Sub, Index, IndexMut};
+use std::ops::{Add, Sub, Mul, Index, IndexMut};
+use std::iter::FromIterator;
 use std::fmt;
+use std::mem;
 use std::cmp::Ordering;
 use rand::distributions::{IndependentSample, Range};
-use rand::{Rng, ThreadRng, thread_rng};
+use rand::{self, Rng};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CatanGame {
     board: Board,
     players: Vec<Player>,
     current_player_index: u32,
+    #[serde(skip)]
     dice: [Dice; 2],
     resource_bank: ResourceCollection,
+    phase: GamePhase,
+    longest_road: Option<PlayerColor>,
+    largest_army: Option<PlayerColor>,
+    turn: TurnState,
+    settings: GameSettings,
+    trades: TradeBook,
+    development_deck: Vec<DevelopmentCardType>,
+    rng: GameRng,
+    history: ActionLog,
+    /// Actions taken back with `undo`, most recent last.
+    undone: Vec<LogEntry>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct GameSettings {
+    pub victory_point_target: u32,
+    /// Every random outcome in the game follows from this seed.
+    pub seed: u64,
+    pub board: BoardLayout,
+}
+
+impl Default for GameSettings {
+    fn default() -> GameSettings {
+        GameSettings {
+            victory_point_target: VICTORY_POINT_TARGET,
+            seed: rand::random(),
+            board: BoardLayout::Balanced,
+        }
+    }
+}
+
+/// Bookkeeping for the current turn, reset whenever a new turn starts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TurnState {
+    rolled: bool,
+    development_card_played: bool,
+    #[serde(with = "pairs")]
+    development_cards_bought: HashMap<DevelopmentCardType, u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     color: PlayerColor,
     resources: ResourceCollection,
-    development_cards: HashMap<DevelopmentCardType, u32>
+    #[serde(with = "pairs")]
+    development_cards: HashMap<DevelopmentCardType, u32>,
+    knights_played: u32,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PlayerColor {
     Red,
     White,
     Orange,
     Blue,
+    Green,
+    Brown,
 }
 
-#[derive(Debug)]
-pub enum PlayerAction<'a> {
-    Roll(u32),
-    Build(BuildingType),
-    PurchaseDevelopmentCard(DevelopmentCardType),
-    PlayDevelopmentCard(DevelopmentCardType),
-    TradeResources(PlayerTrade<'a>),
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum PlayerAction {
+    RollDice,
+    BuildRoad(InternalEdge),
+    BuildSettlement(InternalCoord),
+    BuildCity(InternalCoord),
+    PurchaseDevelopmentCard,
+    PlayDevelopmentCard(DevelopmentCardPlay),
+    /// Offers cards to one player, or to everyone, in exchange for others.
+    OfferTrade(Option<PlayerColor>, ResourceCollection, ResourceCollection),
+    /// Answers an offer with different terms, made back to its proposer.
+    CounterTrade(TradeOfferId, ResourceCollection, ResourceCollection),
+    RespondToTrade(TradeOfferId, TradeResponse),
+    /// Carries out an offer with the named player.
+    ConfirmTrade(TradeOfferId, PlayerColor),
+    /// Cards given to the bank, then cards taken from it.
+    MaritimeTrade(ResourceCollection, ResourceCollection),
+    Discard(ResourceCollection),
+    MoveRobber(InternalCoord, Option<PlayerColor>),
+    EndTurn,
 }
 
-#[derive(Debug)]
-pub struct PlayerTrade<'a> {
-    offering_player: &'a Player,
-    accepting_player: &'a Player,
-    offer: ResourceCollection,
-    receipt: ResourceCollection,
+impl PlayerAction {
+    pub fn kind(&self) -> ActionKind {
+        match *self {
+            PlayerAction::RollDice => ActionKind::RollDice,
+            PlayerAction::BuildRoad(_) => ActionKind::BuildRoad,
+            PlayerAction::BuildSettlement(_) => ActionKind::BuildSettlement,
+            PlayerAction::BuildCity(_) => ActionKind::BuildCity,
+            PlayerAction::PurchaseDevelopmentCard => ActionKind::PurchaseDevelopmentCard,
+            PlayerAction::PlayDevelopmentCard(_) => ActionKind::PlayDevelopmentCard,
+            PlayerAction::OfferTrade(..) |
+            PlayerAction::ConfirmTrade(..) |
+            PlayerAction::MaritimeTrade(..) => ActionKind::TradeResources,
+            PlayerAction::CounterTrade(..) |
+            PlayerAction::RespondToTrade(..) => ActionKind::RespondToTrade,
+            PlayerAction::Discard(_) => ActionKind::Discard,
+            PlayerAction::MoveRobber(..) => ActionKind::MoveRobber,
+            PlayerAction::EndTurn => ActionKind::EndTurn,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum DevelopmentCardType {
+/// A development card being played, along with the choices it needs.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum DevelopmentCardPlay {
     Knight,
-    Progress(DevelopmentProgressType),
-    VictoryPoint(DevelopmentVictoryPointType),
+    /// The second road may only be left out if there is nowhere to put it.
+    RoadBuilding(InternalEdge, Option<InternalEdge>),
+    Monopoly(ResourceType),
+    YearOfPlenty(ResourceType, ResourceType),
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum DevelopmentProgressType {
-    RoadBuilding,
-    Monopoly,
-    YearOfPlenty,
+impl DevelopmentCardPlay {
+    pub fn card(&self) -> DevelopmentCardType {
+        match *self {
+            DevelopmentCardPlay::Knight => DevelopmentCardType::Knight,
+            DevelopmentCardPlay::RoadBuilding(..) => {
+                DevelopmentCardType::Progress(DevelopmentProgressType::RoadBuilding)
+            }
+            DevelopmentCardPlay::Monopoly(_) => {
+                DevelopmentCardType::Progress(DevelopmentProgressType::Monopoly)
+            }
+            DevelopmentCardPlay::YearOfPlenty(..) => {
+                DevelopmentCardType::Progress(DevelopmentProgressType::YearOfPlenty)
+            }
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum DevelopmentVictoryPointType {
-    Chapel,
-    Library,
-    GreatHall,
-    Market,
-    University,
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum GameEvent {
+    DiceRolled(u32, u32),
+    ResourcesProduced(PlayerColor, ResourceCollection),
+    RoadPlaced(PlayerColor, InternalEdge),
+    BuildingPlaced(PlayerColor, BuildingType, InternalCoord),
+    /// The card is `None` once redacted for anyone but the buyer.
+    DevelopmentCardPurchased(PlayerColor, Option<DevelopmentCardType>),
+    ResourcesDiscarded(PlayerColor, ResourceCollection),
+    RobberMoved(InternalCoord),
+    /// The thief, the victim and the resource taken, which is `None` once
+    /// redacted for anyone but the two of them.
+    ResourceStolen(PlayerColor, PlayerColor, Option<ResourceType>),
+    DevelopmentCardPlayed(PlayerColor, DevelopmentCardType),
+    ResourcesMonopolized(PlayerColor, ResourceType, u32),
+    ResourcesTakenFromBank(PlayerColor, ResourceCollection),
+    TradedWithBank(PlayerColor, ResourceCollection, ResourceCollection),
+    TradeOffered(TradeOffer),
+    TradeResponded(TradeOfferId, PlayerColor, TradeResponse),
+    /// The offer's proposer traded with the other player named.
+    TradeExecuted(TradeOfferId, PlayerColor, PlayerColor),
+    TradeOffersExpired(Vec<TradeOfferId>),
+    LongestRoadChanged(Option<PlayerColor>),
+    LargestArmyChanged(Option<PlayerColor>),
+    PhaseChanged(GamePhase),
+    TurnStarted(PlayerColor),
+    /// The named player may build out of turn during the Special Building
+    /// Phase.
+    SpecialBuildingStarted(PlayerColor),
+    GameWon(PlayerColor),
 }
 
-impl GameResource for DevelopmentCardType {
-    fn count(self) -> usize {
-        match self {
-            DevelopmentCardType::Knight => 14,
-            DevelopmentCardType::Progress(DevelopmentProgressType::RoadBuilding) => 2,
-            DevelopmentCardType::Progress(DevelopmentProgressType::Monopoly) => 2,
-            DevelopmentCardType::Progress(DevelopmentProgressType::YearOfPlenty) => 2,
-            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Chapel) => 1,
-            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Library) => 1,
-            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::GreatHall) => 1,
-            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Market) => 1,
-            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::University) => 1,
+impl GameEvent {
+    /// The event as `viewer` may see it. `CatanGame::apply` returns events
+    /// with everything filled in, so anything sent to other players should
+    /// go through this first.
+    pub fn redacted_for(&self, viewer: PlayerColor) -> GameEvent {
+        match *self {
+            GameEvent::DevelopmentCardPurchased(buyer, _) if buyer != viewer => {
+                GameEvent::DevelopmentCardPurchased(buyer, None)
+            }
+            GameEvent::ResourceStolen(thief, victim, _) if thief != viewer && victim != viewer => {
+                GameEvent::ResourceStolen(thief, victim, None)
+            }
+            ref event => event.clone(),
         }
     }
+}
 
-    fn all_variants() -> HashSet<DevelopmentCardType> {
-        let mut variants: HashSet<DevelopmentCardType> = HashSet::new();
+pub const VICTORY_POINT_TARGET: u32 = 10;
+pub const MINIMUM_LARGEST_ARMY: u32 = 3;
+pub const EXTENDED_BANK_SIZE: u32 = 24;
 
-        variants.insert(DevelopmentCardType::Knight);
-        variants.insert(DevelopmentCardType::Progress(
-            DevelopmentProgressType::RoadBuilding,
-        ));
-        variants.insert(DevelopmentCardType::Progress(
-            DevelopmentProgressType::Monopoly,
-        ));
-        variants.insert(DevelopmentCardType::Progress(
-            DevelopmentProgressType::YearOfPlenty,
-        ));
-        variants.insert(DevelopmentCardType::VictoryPoint(
-            DevelopmentVictoryPointType::Chapel,
-        ));
-        variants.insert(DevelopmentCardType::VictoryPoint(
-            DevelopmentVictoryPointType::Library,
-        ));
-        variants.insert(DevelopmentCardType::VictoryPoint(
-            DevelopmentVictoryPointType::GreatHall,
-        ));
-        variants.insert(DevelopmentCardType::VictoryPoint(
-            DevelopmentVictoryPointType::Market,
-        ));
-        variants.insert(DevelopmentCardType::VictoryPoint(
-            DevelopmentVictoryPointType::University,
-        ));
+impl CatanGame {
+    pub fn new(colors: &[PlayerColor]) -> CatanGame {
+        CatanGame::with_settings(colors, GameSettings::default())
+            .expect("The balanced board can always be built!")
+    }
 
-        variants
+    /// Fails if `settings` asks for a random board whose constraints can't
+    /// be met, or for a map that isn't valid.
+    pub fn with_settings(
+        colors: &[PlayerColor],
+        settings: GameSettings,
+    ) -> RulesResult<CatanGame> {
+        assert!(
+            colors.len() >= 2 && colors.len() <= 6,
+            "A game requires between 2 and 6 players!"
+        );
+        assert_eq!(
+            colors.iter().collect::<HashSet<_>>().len(),
+            colors.len(),
+            "Player colors must be unique!"
+        );
+
+        let shape = BoardShape::for_player_count(colors.len());
+        let mut rng = GameRng::from_seed(settings.seed);
+        let board = settings.board.build(&mut rng, shape)?;
+        let development_deck = match shape {
+            BoardShape::Standard => DevelopmentCardType::full_shuffled_collection(&mut rng),
+            BoardShape::Extended => {
+                DevelopmentCardType::shuffled_collection(
+                    &mut rng,
+                    DevelopmentCardType::extended_count,
+                )
+            }
+        };
+
+        let history = ActionLog::new(colors, settings.clone());
+
+        Ok(CatanGame {
+            board,
+            players: colors.iter().map(|&color| Player::new(color)).collect(),
+            current_player_index: 0,
+            dice: [Dice::new(), Dice::new()],
+            resource_bank: ResourceCollection::supply(shape),
+            phase: GamePhase::Setup(SetupRound::First, SetupStep::Settlement),
+            longest_road: None,
+            largest_army: None,
+            turn: TurnState::default(),
+            settings,
+            trades: TradeBook::default(),
+            development_deck,
+            rng,
+            history,
+            undone: Vec::new(),
+        })
     }
-}
 
-#[derive(Debug)]
-pub struct Dice {
-    _range: Range<u32>,
-    _rng: ThreadRng,
-}
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
 
-impl Dice {
-    fn new() -> Dice {
-        Dice {
-            _range: Range::new(1, 7),
-            _rng: thread_rng(),
+    /// Every action applied so far, enough to rebuild the game with
+    /// `CatanGame::replay`.
+    pub fn history(&self) -> &ActionLog {
+        &self.history
+    }
+
+    /// Takes back the last action by replaying the game without it. Actions
+    /// that rolled the dice, drew a development card or stole a resource
+    /// can't be taken back, and neither can anything before them.
+    pub fn undo(&mut self) -> RulesResult<()> {
+        let last = match self.history.entries().last() {
+            Some(last) => last.clone(),
+            None => return Err(RulesError::NothingToUndo),
+        };
+        if !last.outcomes.is_empty() {
+            return Err(RulesError::OutcomeRevealed);
         }
+
+        let mut undone = mem::take(&mut self.undone);
+        undone.push(last);
+        *self = CatanGame::replay_to(&self.history, self.history.len() - 1)
+            .expect("A game's own history should always replay!");
+        self.undone = undone;
+
+        Ok(())
     }
 
-    fn roll(&mut self) -> u32 {
-        self._range.ind_sample(&mut self._rng)
+    /// Applies the most recently undone action again.
+    pub fn redo(&mut self) -> RulesResult<Vec<GameEvent>> {
+        let entry = match self.undone.pop() {
+            Some(entry) => entry,
+            None => return Err(RulesError::NothingToRedo),
+        };
+
+        let undone = mem::take(&mut self.undone);
+        let events = self.apply(entry.color, entry.action)?;
+        self.undone = undone;
+
+        Ok(events)
     }
-}
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum ResourceType {
-    Ore,
-    Brick,
-    Grain,
-    Wool,
-    Lumber,
-}
+    pub fn can_undo(&self) -> bool {
+        self.history.entries().last().is_some_and(|last| last.outcomes.is_empty())
+    }
 
-impl GameResource for ResourceType {
-    fn count(self) -> usize {
-        match self {
-            ResourceType::Ore => 19,
-            ResourceType::Brick => 19,
-            ResourceType::Grain => 19,
-            ResourceType::Wool => 19,
-            ResourceType::Lumber => 19,
-        }
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
     }
 
-    fn all_variants() -> HashSet<ResourceType> {
-        let mut variants: HashSet<ResourceType> = HashSet::new();
+    /// Games with five or six players use the extension board and supplies.
+    pub fn board_shape(&self) -> BoardShape {
+        BoardShape::for_player_count(self.players.len())
+    }
 
-        variants.insert(ResourceType::Ore);
-        variants.insert(ResourceType::Brick);
-        variants.insert(ResourceType::Grain);
-        variants.insert(ResourceType::Wool);
-        variants.insert(ResourceType::Lumber);
+    pub fn trade_offers(&self) -> &[TradeOffer] {
+        self.trades.offers()
+    }
 
-        variants
+    /// How many cards are left to buy. Which cards they are stays hidden.
+    pub fn development_deck_size(&self) -> usize {
+        self.development_deck.len()
     }
-}
 
-#[derive(PartialEq, Eq)]
-pub struct ResourceCollection {
-    ore: u32,
-    brick: u32,
-    grain: u32,
-    wool: u32,
-    lumber: u32,
-}
+    /// How many development cards each player holds, which is all that
+    /// opponents are allowed to know about a hand.
+    pub fn development_card_counts(&self) -> HashMap<PlayerColor, u32> {
+        self.players
+            .iter()
+            .map(|player| (player.color, player.development_card_count()))
+            .collect()
+    }
 
-impl PartialOrd for ResourceCollection {
-    fn partial_cmp(&self, other: &ResourceCollection) -> Option<Ordering> {
-        if self.ore > other.ore && self.brick > other.brick && self.grain > other.grain &&
-            self.wool > other.wool && self.lumber > other.lumber
-        {
-            Some(Ordering::Greater)
-        } else if self.ore < other.ore && self.brick < other.brick &&
-                   self.grain < other.grain && self.wool < other.wool &&
-                   self.lumber < other.lumber
-        {
-            Some(Ordering::Less)
-        } else if self == other {
-            Some(Ordering::Equal)
-        } else {
-            None
-        }
+    /// Whether `color` bought `card` this turn, which means it can't be
+    /// played until their next one.
+    pub fn bought_this_turn(&self, color: PlayerColor, card: DevelopmentCardType) -> bool {
+        color == self.current_player() &&
+            self.turn.development_cards_bought.contains_key(&card)
     }
-}
 
-impl ResourceCollection {
-    pub fn new(ore: u32, brick: u32, grain: u32, wool: u32, lumber: u32) -> ResourceCollection {
-        ResourceCollection {
-            ore,
-            brick,
-            grain,
-            wool,
-            lumber,
-        }
+    pub fn board(&self) -> &Board {
+        &self.board
     }
 
-    pub fn satisfies(&self, other: &ResourceCollection) -> bool {
-        self.ore >= other.ore && self.brick >= other.brick && self.grain >= other.grain &&
-            self.wool >= other.wool && self.lumber >= other.lumber
+    pub fn players(&self) -> &[Player] {
+        &self.players
     }
 
-    pub fn magnitude(&self) -> u32 {
-        self.ore + self.brick + self.grain + self.wool + self.lumber
+    pub fn player(&self, color: PlayerColor) -> Option<&Player> {
+        self.players.iter().find(|player| player.color == color)
     }
-}
 
-impl fmt::Debug for ResourceCollection {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "(Ore: {}, Brick: {}, Grain: {}, Wool: {}, Lumber: {})",
-            self.ore,
-            self.brick,
-            self.grain,
-            self.wool,
-            self.lumber
-        )
+    pub fn current_player(&self) -> PlayerColor {
+        self.players[self.current_player_index as usize].color
     }
-}
 
-impl Add for ResourceCollection {
-    type Output = ResourceCollection;
+    pub fn resource_bank(&self) -> &ResourceCollection {
+        &self.resource_bank
+    }
 
-    fn add(self, other: ResourceCollection) -> ResourceCollection {
-        ResourceCollection {
-            ore: self.ore + other.ore,
-            brick: self.brick + other.brick,
-            grain: self.grain + other.grain,
-            wool: self.wool + other.wool,
-            lumber: self.lumber + other.lumber,
-        }
+    pub fn phase(&self) -> &GamePhase {
+        &self.phase
     }
-}
 
-impl Sub for ResourceCollection {
-    type Output = ResourceCollection;
+    pub fn longest_road_holder(&self) -> Option<PlayerColor> {
+        self.longest_road
+    }
 
-    fn sub(self, other: ResourceCollection) -> ResourceCollection {
-        ResourceCollection {
-            ore: self.ore - other.ore,
-            brick: self.brick - other.brick,
-            grain: self.grain - other.grain,
-            wool: self.wool - other.wool,
-            lumber: self.lumber - other.lumber,
+    pub fn largest_army_holder(&self) -> Option<PlayerColor> {
+        self.largest_army
+    }
+
+    pub fn allowed_actions(&self, color: PlayerColor) -> Vec<ActionKind> {
+        match self.phase {
+            GamePhase::Discard(ref pending) if pending.contains_key(&color) => {
+                vec![ActionKind::Discard]
+            }
+            // The current player may turn down or counter offers made back to
+            // them, such as counter-offers to their own
+            GamePhase::Main if color == self.current_player() => {
+                let mut actions = self.phase.current_player_actions();
+                if self.has_offer_open_to(color) {
+                    actions.push(ActionKind::RespondToTrade);
+                }
+                actions
+            }
+            ref phase if color == self.current_player() => phase.current_player_actions(),
+            // Other players may answer offers made to them
+            GamePhase::Main if self.has_offer_open_to(color) => vec![ActionKind::RespondToTrade],
+            _ => Vec::new(),
         }
     }
-}
 
-impl Index<ResourceType> for ResourceCollection {
-    type Output = u32;
+    /// Every concrete action `color` could apply right now without it being
+    /// rejected. New offers to other players and counter-offers have terms
+    /// that are up to the player, so they are not enumerated.
+    pub fn legal_actions(&self, color: PlayerColor) -> Vec<PlayerAction> {
+        let mut actions = Vec::new();
 
-    fn index(&self, index: ResourceType) -> &u32 {
-        match index {
-            ResourceType::Ore => &self.ore,
-            ResourceType::Brick => &self.brick,
-            ResourceType::Grain => &self.grain,
-            ResourceType::Wool => &self.wool,
-            ResourceType::Lumber => &self.lumber,            
+        for kind in self.allowed_actions(color) {
+            match kind {
+                ActionKind::RollDice => actions.push(PlayerAction::RollDice),
+                ActionKind::BuildRoad => {
+                    actions.extend(
+                        self.board
+                            .edges()
+                            .into_iter()
+                            .filter(|edge| self.check_build_road(color, *edge).is_ok())
+                            .map(PlayerAction::BuildRoad),
+                    )
+                }
+                ActionKind::BuildSettlement => {
+                    actions.extend(
+                        self.board
+                            .building_tiles()
+                            .into_iter()
+                            .filter(|coordinate| {
+                                self.check_build_settlement(color, *coordinate).is_ok()
+                            })
+                            .map(PlayerAction::BuildSettlement),
+                    )
+                }
+                ActionKind::BuildCity => {
+                    actions.extend(
+                        self.board
+                            .buildings_of(color, BuildingType::Settlement)
+                            .into_iter()
+                            .filter(|coordinate| self.check_build_city(color, *coordinate).is_ok())
+                            .map(PlayerAction::BuildCity),
+                    )
+                }
+                ActionKind::PurchaseDevelopmentCard => {
+                    if self.check_purchase_development_card(color).is_ok() {
+                        actions.push(PlayerAction::PurchaseDevelopmentCard);
+                    }
+                }
+                ActionKind::Discard => {
+                    let player = self.player(color).expect("Player color was already validated!");
+                    actions.extend(
+                        player
+                            .resources
+                            .sub_collections(self.required_discard(color))
+                            .into_iter()
+                            .map(PlayerAction::Discard),
+                    )
+                }
+                ActionKind::MoveRobber => {
+                    for coordinate in self.board.resource_tiles() {
+                        if self.board.is_blocked(coordinate) {
+                            continue;
+                        }
+
+                        let targets = self.steal_targets(color, coordinate);
+                        if targets.is_empty() {
+                            actions.push(PlayerAction::MoveRobber(coordinate, None));
+                        } else {
+                            actions.extend(targets.into_iter().map(|victim| {
+                                PlayerAction::MoveRobber(coordinate, Some(victim))
+                            }));
+                        }
+                    }
+                }
+                ActionKind::PlayDevelopmentCard => {
+                    actions.extend(
+                        self.development_card_plays(color)
+                            .into_iter()
+                            .filter(|play| self.check_play_development_card(color, *play).is_ok())
+                            .map(PlayerAction::PlayDevelopmentCard),
+                    )
+                }
+                ActionKind::EndTurn => actions.push(PlayerAction::EndTurn),
+                ActionKind::TradeResources => {
+                    actions.extend(self.maritime_trades(color));
+                    actions.extend(self.trade_confirmations(color));
+                }
+                ActionKind::RespondToTrade => {
+                    for offer in self.trades.offers() {
+                        for &response in [TradeResponse::Accept, TradeResponse::Reject].iter() {
+                            if self.check_respond_to_trade(color, offer.id(), response).is_ok() {
+                                actions.push(PlayerAction::RespondToTrade(offer.id(), response));
+                            }
+                        }
+                    }
+                }
+            }
         }
+
+        actions
     }
-}
 
-impl IndexMut<ResourceType> for ResourceCollection {
-    fn index_mut(&mut self, index: ResourceType) -> &mut Self::Output {
-        match index {
-            ResourceType::Ore => &mut self.ore,
-            ResourceType::Brick => &mut self.brick,
-            ResourceType::Grain => &mut self.grain,
-            ResourceType::Wool => &mut self.wool,
-            ResourceType::Lumber => &mut self.lumber,            
+    pub fn apply(
+        &mut self,
+        color: PlayerColor,
+        action: PlayerAction,
+    ) -> RulesResult<Vec<GameEvent>> {
+        if self.player(color).is_none() {
+            return Err(RulesError::UnknownPlayer(color));
+        }
+        if let GamePhase::GameOver(_) = self.phase {
+            return Err(RulesError::GameOver);
+        }
+
+        let allowed_actions = self.allowed_actions(color);
+        if allowed_actions.is_empty() {
+            return Err(RulesError::NotYourTurn(color));
         }
+        if !allowed_actions.contains(&action.kind()) {
+            return Err(RulesError::ActionNotAllowed(action.kind()));
+        }
+
+        debug!("{:?} is applying {:?}", color, action);
+
+        let logged_action = action.clone();
+        let mut events = match action {
+            PlayerAction::RollDice => self.roll_dice(),
+            PlayerAction::BuildRoad(edge) => self.build_road(color, edge),
+            PlayerAction::BuildSettlement(coordinate) => self.build_settlement(color, coordinate),
+            PlayerAction::BuildCity(coordinate) => self.build_city(color, coordinate),
+            PlayerAction::PurchaseDevelopmentCard => self.purchase_development_card(color),
+            PlayerAction::PlayDevelopmentCard(play) => self.play_development_card(color, play),
+            PlayerAction::OfferTrade(to, offer, request) => {
+                self.offer_trade(color, to, offer, request)
+            }
+            PlayerAction::CounterTrade(id, offer, request) => {
+                self.counter_trade(color, id, offer, request)
+            }
+            PlayerAction::RespondToTrade(id, response) => {
+                self.respond_to_trade(color, id, response)
+            }
+            PlayerAction::ConfirmTrade(id, partner) => self.confirm_trade(color, id, partner),
+            PlayerAction::MaritimeTrade(offer, receipt) => {
+                self.trade_with_bank(color, offer, receipt)
+            }
+            PlayerAction::Discard(resources) => self.discard(color, resources),
+            PlayerAction::MoveRobber(coordinate, victim) => {
+                self.move_robber(color, coordinate, victim)
+            }
+            PlayerAction::EndTurn => self.end_turn(),
+        }?;
+
+        // Only the current player can win, and only on their own turn, even
+        // if someone else's action pushed them over the target. Building out
+        // of turn can't win either; that waits for the builder's next turn.
+        let current_player = self.current_player();
+        let own_turn = !matches!(self.phase, GamePhase::SpecialBuilding(_));
+        if !self.phase.is_setup() && own_turn &&
+            self.true_score(current_player) >= self.settings.victory_point_target
+        {
+            events.extend(self.set_phase(GamePhase::GameOver(current_player)));
+            events.push(GameEvent::GameWon(current_player));
+        }
+
+        self.history.push(LogEntry {
+            color,
+            action: logged_action,
+            outcomes: RandomOutcome::from_events(&events),
+        });
+        self.undone.clear();
+
+        Ok(events)
     }
-}
 
-#[cfg(test)]
-mod resource_collection_tests {
-    use catan::game::ResourceCollection;
-    use catan::game::ResourceType;
+    /// What each player would collect if `roll` were rolled now, limited by
+    /// the bank's stock. Players who would collect nothing are left out.
+    pub fn production(&self, roll: u32) -> HashMap<PlayerColor, ResourceCollection> {
+        apply_shortage_rule(owed_resources(&self.board, roll), &self.resource_bank)
+    }
 
-    #[test]
-    fn test_creation() {
-        let resource_collection = ResourceCollection::new(3, 2, 1, 0, 4);
+    /// Opponents of `color` who could be robbed by moving the robber to
+    /// `coordinate`: they own a building on that hex and hold at least one card.
+    pub fn steal_targets(&self, color: PlayerColor, coordinate: InternalCoord) -> Vec<PlayerColor> {
+        let owners: HashSet<PlayerColor> = self.board
+            .adjacent_building_tiles(coordinate)
+            .into_iter()
+            .filter_map(|vertex| self.board.building_at(vertex))
+            .map(|(owner, _)| owner)
+            .collect();
 
-        assert_eq!(resource_collection.ore, 3);
-        assert_eq!(resource_collection.brick, 2);
-        assert_eq!(resource_collection.grain, 1);
-        assert_eq!(resource_collection.wool, 0);
-        assert_eq!(resource_collection.lumber, 4);
+        self.players
+            .iter()
+            .filter(|player| {
+                player.color != color && owners.contains(&player.color) &&
+                    player.resources.magnitude() > 0
+            })
+            .map(|player| player.color)
+            .collect()
     }
 
-    #[test]
-    fn test_satisfies() {
-        let collection_a = ResourceCollection::new(3, 2, 1, 0, 4);
-        let does_satisfy = ResourceCollection::new(3, 1, 0, 0, 2);
-        let does_not_satisfy = ResourceCollection::new(2, 2, 2, 2, 2);
+    /// How many cards of their choice `color` would get from the bank for
+    /// `offer`, limited by what the bank holds of the resources not offered.
+    pub fn maritime_quote(&self, color: PlayerColor, offer: ResourceCollection) -> u32 {
+        let value = maritime_value(&maritime_rates(&self.board, color), &offer);
+        let available: u32 = ALL_RESOURCE_TYPES
+            .iter()
+            .filter(|resource_type| offer[**resource_type] == 0)
+            .map(|resource_type| self.resource_bank[*resource_type])
+            .sum();
 
-        assert!(collection_a.satisfies(&does_satisfy));
-        assert!(!collection_a.satisfies(&does_not_satisfy));
+        value.min(available)
     }
 
-    #[test]
-    fn test_equality() {
-        let equal_a = ResourceCollection::new(1, 2, 1, 1, 1);
-        let equal_b = ResourceCollection::new(1, 2, 1, 1, 1);
-        let not_equal = ResourceCollection::new(1, 1, 1, 2, 1);
+    /// Victory points every player can see: buildings plus the Longest Road
+    /// and Largest Army cards.
+    pub fn public_score(&self, color: PlayerColor) -> u32 {
+        let settlements = self.board
+            .buildings_of(color, BuildingType::Settlement)
+            .len();
+        let cities = self.board.buildings_of(color, BuildingType::City).len();
+        let longest_road = if self.longest_road == Some(color) { 2 } else { 0 };
+        let largest_army = if self.largest_army == Some(color) { 2 } else { 0 };
 
-        assert!(equal_a == equal_b);
-        assert!(equal_a != not_equal);
-        assert!(equal_b != not_equal);
+        (settlements + 2 * cities) as u32 + longest_road + largest_army
     }
 
-    #[test]
-    fn test_comparison() {
-        let lower_collection = ResourceCollection::new(0, 1, 2, 3, 4);
-        let middle_collection = ResourceCollection::new(1, 2, 3, 4, 5);
-        let upper_collection = ResourceCollection::new(2, 3, 4, 5, 6);
+    /// The public score plus any victory point cards `color` is holding.
+    pub fn true_score(&self, color: PlayerColor) -> u32 {
+        let hidden: u32 = self.player(color)
+            .map(|player| {
+                player
+                    .development_cards
+                    .iter()
+                    .filter(|&(card, _)| matches!(*card, DevelopmentCardType::VictoryPoint(_)))
+                    .map(|(_, &count)| count)
+                    .sum()
+            })
+            .unwrap_or(0);
 
-        let lower_middle_collection = ResourceCollection::new(0, 1, 2, 4, 5);
-        let middle_upper_collection = ResourceCollection::new(1, 2, 3, 5, 6);
+        self.public_score(color) + hidden
+    }
 
-        // Normal orderings
-        assert!(lower_collection < middle_collection);
-        assert!(middle_collection < upper_collection);
-        assert!(lower_collection < middle_collection);
+    fn player_mut(&mut self, color: PlayerColor) -> &mut Player {
+        self.players
+            .iter_mut()
+            .find(|player| player.color == color)
+            .expect("Player color was already validated!")
+    }
 
-        // Incomplete orderings, no order can be produced so everything is false
-        assert!(!(lower_collection < lower_middle_collection));
-        assert!(!(lower_collection > lower_middle_collection));
-        assert!(!(lower_collection == lower_middle_collection));
+    fn set_phase(&mut self, phase: GamePhase) -> Vec<GameEvent> {
+        self.phase = phase;
 
-        assert!(!(lower_middle_collection < middle_collection));
-        assert!(!(lower_middle_collection > middle_collection));
-        assert!(!(lower_middle_collection == middle_collection));
+        vec![GameEvent::PhaseChanged(self.phase.clone())]
+    }
 
-        assert!(!(middle_collection < middle_upper_collection));
-        assert!(!(middle_collection > middle_upper_collection));
-        assert!(!(middle_collection == middle_upper_collection));
+    fn start_turn(&mut self, player_index: u32) -> Vec<GameEvent> {
+        self.current_player_index = player_index;
+        self.turn = TurnState::default();
 
-        assert!(!(middle_upper_collection < upper_collection));
-        assert!(!(middle_upper_collection > upper_collection));
-        assert!(!(middle_upper_collection == upper_collection));
+        vec![GameEvent::TurnStarted(self.current_player())]
     }
 
-    #[test]
-    fn test_addition() {
-        let collection_a = ResourceCollection::new(0, 0, 1, 1, 1);
-        let collection_b = ResourceCollection::new(2, 1, 0, 1, 2);
-        let result = ResourceCollection::new(2, 1, 1, 2, 3);
+    fn start_special_building(&mut self, player_index: u32) -> Vec<GameEvent> {
+        self.current_player_index = player_index;
+        self.turn = TurnState::default();
 
-        assert_eq!(collection_a + collection_b, result);
+        vec![GameEvent::SpecialBuildingStarted(self.current_player())]
     }
 
-    #[test]
-    fn test_subtraction() {
-        let collection_a = ResourceCollection::new(2, 1, 1, 2, 3);
-        let collection_b = ResourceCollection::new(2, 1, 0, 1, 2);
-        let result = ResourceCollection::new(0, 0, 1, 1, 1);
+    fn check_affordable(&self, color: PlayerColor, cost: ResourceCollection) -> RulesResult<()> {
+        let player = self.player(color).expect("Player color was already validated!");
+        let mut resources = player.resources;
 
-        assert_eq!(collection_a - collection_b, result);
+        Ok(resources.try_pay(cost)?)
     }
 
-    #[test]
-    fn test_indexing() {
-        let collection = ResourceCollection::new(2, 3, 5, 7, 9);
+    /// Moves `cost` from `color`'s hand to the bank, which can never hold
+    /// more than the game's supply.
+    fn pay_to_bank(&mut self, color: PlayerColor, cost: ResourceCollection) -> RulesResult<()> {
+        let mut resources = self.player(color)
+            .expect("Player color was already validated!")
+            .resources;
+        resources.try_pay(cost)?;
+        let supply = ResourceCollection::supply(self.board_shape());
+        let bank = self.resource_bank
+            .capped_add(cost, supply)
+            .ok_or(RulesError::BankSupplyExceeded)?;
+
+        self.player_mut(color).resources = resources;
+        self.resource_bank = bank;
+
+        Ok(())
+    }
+
+    /// Hands `resources` from the bank to `color`, or nothing at all if the
+    /// bank can't cover every card.
+    fn take_from_bank(
+        &mut self,
+        color: PlayerColor,
+        resources: ResourceCollection,
+    ) -> RulesResult<()> {
+        self.resource_bank.try_pay(resources).map_err(|_| RulesError::BankExhausted)?;
+        let player = self.player_mut(color);
+        player.resources = player.resources + resources;
+
+        Ok(())
+    }
+
+    fn roll_dice(&mut self) -> RulesResult<Vec<GameEvent>> {
+        let first = self.dice[0].roll(&mut self.rng);
+        let second = self.dice[1].roll(&mut self.rng);
+
+        self.turn.rolled = true;
+
+        let mut events = vec![GameEvent::DiceRolled(first, second)];
+        if first + second == 7 {
+            let pending = self.pending_discards();
+            if pending.is_empty() {
+                events.extend(self.set_phase(GamePhase::MoveRobber));
+            } else {
+                events.extend(self.set_phase(GamePhase::Discard(pending)));
+            }
+        } else {
+            events.extend(self.produce_resources(first + second));
+            events.extend(self.set_phase(GamePhase::Main));
+        }
+
+        Ok(events)
+    }
+
+    fn pending_discards(&self) -> HashMap<PlayerColor, u32> {
+        self.players
+            .iter()
+            .filter(|player| player.resources.magnitude() > 7)
+            .map(|player| (player.color, player.resources.magnitude() / 2))
+            .collect()
+    }
+
+    fn required_discard(&self, color: PlayerColor) -> u32 {
+        match self.phase {
+            GamePhase::Discard(ref pending) => pending[&color],
+            _ => unreachable!("Discards are only allowed in the discard phase"),
+        }
+    }
+
+    fn check_discard(&self, color: PlayerColor, resources: ResourceCollection) -> RulesResult<()> {
+        let required = self.required_discard(color);
+        if resources.magnitude() != required {
+            return Err(RulesError::WrongDiscardAmount(required));
+        }
+
+        self.check_affordable(color, resources)
+    }
+
+    fn discard(
+        &mut self,
+        color: PlayerColor,
+        resources: ResourceCollection,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_discard(color, resources)?;
+        self.pay_to_bank(color, resources)?;
+
+        let mut pending = match self.phase {
+            GamePhase::Discard(ref pending) => pending.clone(),
+            _ => unreachable!("Discards are only allowed in the discard phase"),
+        };
+        pending.remove(&color);
+
+        let mut events = vec![GameEvent::ResourcesDiscarded(color, resources)];
+        if pending.is_empty() {
+            events.extend(self.set_phase(GamePhase::MoveRobber));
+        } else {
+            self.phase = GamePhase::Discard(pending);
+        }
+
+        Ok(events)
+    }
+
+    fn check_move_robber(
+        &self,
+        color: PlayerColor,
+        coordinate: InternalCoord,
+        victim: Option<PlayerColor>,
+    ) -> RulesResult<()> {
+        match self.board.tiles.get(&coordinate) {
+            Some(&InternalTileType::ResourceTile(_)) => {}
+            _ => return Err(RulesError::InvalidLocation(coordinate)),
+        }
+        if self.board.is_blocked(coordinate) {
+            return Err(RulesError::RobberMustMove);
+        }
+
+        // A victim must be named whenever anyone can be robbed
+        let targets = self.steal_targets(color, coordinate);
+        let valid_victim = match victim {
+            Some(victim) => targets.contains(&victim),
+            None => targets.is_empty(),
+        };
+        if !valid_victim {
+            return Err(RulesError::InvalidStealTarget(victim));
+        }
+
+        Ok(())
+    }
+
+    fn move_robber(
+        &mut self,
+        color: PlayerColor,
+        coordinate: InternalCoord,
+        victim: Option<PlayerColor>,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_move_robber(color, coordinate, victim)?;
+
+        self.board.robber = Some(coordinate);
+        let mut events = vec![GameEvent::RobberMoved(coordinate)];
+        if let Some(victim) = victim {
+            events.extend(self.steal_resource(color, victim));
+        }
+        // A knight played before rolling still leaves the roll to be made
+        if self.turn.rolled {
+            events.extend(self.set_phase(GamePhase::Main));
+        } else {
+            events.extend(self.set_phase(GamePhase::PreRoll));
+        }
+
+        Ok(events)
+    }
+
+    fn steal_resource(&mut self, thief: PlayerColor, victim: PlayerColor) -> Vec<GameEvent> {
+        let resources = self.player(victim)
+            .expect("Steal targets are always players")
+            .resources;
+        let stolen = resources.random_resource(&mut self.rng);
+
+        match stolen {
+            Some(resource_type) => {
+                self.player_mut(victim).resources[resource_type] -= 1;
+                self.player_mut(thief).resources[resource_type] += 1;
+
+                vec![GameEvent::ResourceStolen(thief, victim, Some(resource_type))]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn produce_resources(&mut self, roll: u32) -> Vec<GameEvent> {
+        let mut production = self.production(roll);
+
+        let mut events = Vec::new();
+        for player in self.players.iter_mut() {
+            if let Some(resources) = production.remove(&player.color) {
+                self.resource_bank.try_pay(resources).expect(
+                    "Production is limited to what the bank holds!",
+                );
+                player.resources = player.resources + resources;
+                events.push(GameEvent::ResourcesProduced(player.color, resources));
+            }
+        }
+
+        events
+    }
+
+    fn check_build_road(&self, color: PlayerColor, edge: InternalEdge) -> RulesResult<()> {
+        self.check_road_placement(color, edge)?;
+        if self.phase.is_setup() {
+            return Ok(());
+        }
+
+        self.check_affordable(color, ROAD_COST)
+    }
+
+    /// Everything about building a road except paying for it.
+    fn check_road_placement(&self, color: PlayerColor, edge: InternalEdge) -> RulesResult<()> {
+        self.check_road_placement_on(&self.board, color, edge)
+    }
+
+    /// `check_road_placement` against a board other than the game's own, such
+    /// as one with a road that has yet to be built.
+    fn check_road_placement_on(
+        &self,
+        board: &Board,
+        color: PlayerColor,
+        edge: InternalEdge,
+    ) -> RulesResult<()> {
+        if !board.is_edge(edge) {
+            return Err(RulesError::InvalidEdge(edge));
+        }
+        if board.road_at(edge).is_some() {
+            return Err(RulesError::EdgeOccupied(edge));
+        }
+
+        // Setup roads are free, but must touch the settlement just placed
+        if let GamePhase::Setup(_, SetupStep::Road(settlement)) = self.phase {
+            return if edge.contains(settlement) {
+                Ok(())
+            } else {
+                Err(RulesError::InvalidEdge(edge))
+            };
+        }
+
+        let (a, b) = edge.endpoints();
+        let connected = [a, b].iter().any(|&endpoint| {
+            match board.building_at(endpoint) {
+                Some((owner, _)) => owner == color,
+                // A road may only extend through an endpoint that is not
+                // occupied by an opponent's building.
+                None => {
+                    board.edges_at(endpoint).into_iter().any(|other| {
+                        other != edge && board.road_at(other) == Some(color)
+                    })
+                }
+            }
+        });
+        if !connected {
+            return Err(RulesError::NotConnected);
+        }
+
+        check_pieces_remaining(board, color, BuildingType::Road)
+    }
+
+    fn build_road(
+        &mut self,
+        color: PlayerColor,
+        edge: InternalEdge,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_build_road(color, edge)?;
+
+        let setup_round = match self.phase {
+            GamePhase::Setup(round, _) => Some(round),
+            _ => None,
+        };
+        if setup_round.is_none() {
+            self.pay_to_bank(color, ROAD_COST)?;
+        }
+        self.board.place_road(edge, color);
+
+        let mut events = vec![GameEvent::RoadPlaced(color, edge)];
+        events.extend(self.update_longest_road());
+        if let Some(round) = setup_round {
+            events.extend(self.advance_setup(round));
+        }
+
+        Ok(events)
+    }
+
+    fn check_build_settlement(
+        &self,
+        color: PlayerColor,
+        coordinate: InternalCoord,
+    ) -> RulesResult<()> {
+        match self.board.building_tile(coordinate) {
+            None => return Err(RulesError::InvalidLocation(coordinate)),
+            Some(building_tile) if building_tile.building.is_some() => {
+                return Err(RulesError::LocationOccupied(coordinate))
+            }
+            Some(_) => {}
+        }
+
+        let crowded = self.board
+            .adjacent_building_tiles(coordinate)
+            .into_iter()
+            .any(|neighbor| self.board.building_at(neighbor).is_some());
+        if crowded {
+            return Err(RulesError::DistanceRuleViolated(coordinate));
+        }
+
+        // Setup settlements are free and need no road
+        if self.phase.is_setup() {
+            return Ok(());
+        }
+
+        let touches_road = self.board.edges_at(coordinate).into_iter().any(|edge| {
+            self.board.road_at(edge) == Some(color)
+        });
+        if !touches_road {
+            return Err(RulesError::NotConnected);
+        }
+        check_pieces_remaining(&self.board, color, BuildingType::Settlement)?;
+
+        self.check_affordable(color, SETTLEMENT_COST)
+    }
+
+    fn build_settlement(
+        &mut self,
+        color: PlayerColor,
+        coordinate: InternalCoord,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_build_settlement(color, coordinate)?;
+
+        let setup_round = match self.phase {
+            GamePhase::Setup(round, _) => Some(round),
+            _ => None,
+        };
+        if setup_round.is_none() {
+            self.pay_to_bank(color, SETTLEMENT_COST)?;
+        }
+        self.board.place_building(
+            coordinate,
+            color,
+            BuildingType::Settlement,
+        );
+
+        let mut events = vec![
+            GameEvent::BuildingPlaced(color, BuildingType::Settlement, coordinate),
+        ];
+        // A new settlement can cut through an opponent's road
+        events.extend(self.update_longest_road());
+        if let Some(round) = setup_round {
+            if round == SetupRound::Second {
+                events.extend(self.collect_starting_resources(color, coordinate));
+            }
+            self.phase = GamePhase::Setup(round, SetupStep::Road(coordinate));
+        }
+
+        Ok(events)
+    }
+
+    fn update_longest_road(&mut self) -> Vec<GameEvent> {
+        let lengths: Vec<(PlayerColor, u32)> = self.players
+            .iter()
+            .map(|player| (player.color, longest_road(&self.board, player.color)))
+            .collect();
+
+        let holder = longest_road_holder(&lengths, self.longest_road);
+        if holder == self.longest_road {
+            return Vec::new();
+        }
+        self.longest_road = holder;
+
+        vec![GameEvent::LongestRoadChanged(holder)]
+    }
+
+    fn collect_starting_resources(
+        &mut self,
+        color: PlayerColor,
+        coordinate: InternalCoord,
+    ) -> Vec<GameEvent> {
+        let mut resources = ResourceCollection::default();
+        for tile in self.board.adjacent_resource_tiles(coordinate) {
+            if let InternalTileType::ResourceTile(tile_type) = *self.board.get_location(tile).0 {
+                if let Some(resource_type) = tile_type.into_resource_type() {
+                    resources[resource_type] += 1;
+                }
+            }
+        }
+
+        self.take_from_bank(color, resources).expect(
+            "The bank always holds enough for starting resources!",
+        );
+
+        vec![GameEvent::ResourcesProduced(color, resources)]
+    }
+
+    // Setup is a snake draft: the first round runs in seating order and the
+    // second round runs back the other way, so the last player places twice
+    // in a row and the first player places last.
+    fn advance_setup(&mut self, round: SetupRound) -> Vec<GameEvent> {
+        let last_index = self.players.len() as u32 - 1;
+
+        match round {
+            SetupRound::First if self.current_player_index == last_index => {
+                self.set_phase(GamePhase::Setup(SetupRound::Second, SetupStep::Settlement))
+            }
+            SetupRound::First => {
+                self.phase = GamePhase::Setup(SetupRound::First, SetupStep::Settlement);
+                let next_index = self.current_player_index + 1;
+                self.start_turn(next_index)
+            }
+            SetupRound::Second if self.current_player_index == 0 => {
+                let mut events = self.set_phase(GamePhase::PreRoll);
+                events.extend(self.start_turn(0));
+                events
+            }
+            SetupRound::Second => {
+                self.phase = GamePhase::Setup(SetupRound::Second, SetupStep::Settlement);
+                let next_index = self.current_player_index - 1;
+                self.start_turn(next_index)
+            }
+        }
+    }
+
+    fn check_build_city(&self, color: PlayerColor, coordinate: InternalCoord) -> RulesResult<()> {
+        if self.board.building_at(coordinate) != Some((color, BuildingType::Settlement)) {
+            return Err(RulesError::InvalidLocation(coordinate));
+        }
+        check_pieces_remaining(&self.board, color, BuildingType::City)?;
+
+        self.check_affordable(color, CITY_COST)
+    }
+
+    fn build_city(
+        &mut self,
+        color: PlayerColor,
+        coordinate: InternalCoord,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_build_city(color, coordinate)?;
+        self.pay_to_bank(color, CITY_COST)?;
+
+        self.board.place_building(coordinate, color, BuildingType::City);
+
+        Ok(vec![
+            GameEvent::BuildingPlaced(color, BuildingType::City, coordinate),
+        ])
+    }
+
+    fn check_purchase_development_card(&self, color: PlayerColor) -> RulesResult<()> {
+        if self.development_deck.is_empty() {
+            return Err(RulesError::DevelopmentDeckEmpty);
+        }
+
+        self.check_affordable(color, DEVELOPMENT_CARD_COST)
+    }
+
+    fn purchase_development_card(&mut self, color: PlayerColor) -> RulesResult<Vec<GameEvent>> {
+        self.check_purchase_development_card(color)?;
+        self.pay_to_bank(color, DEVELOPMENT_CARD_COST)?;
+
+        let card = self.development_deck
+            .pop()
+            .expect("Deck was already checked for cards!");
+        *self.player_mut(color)
+            .development_cards
+            .entry(card)
+            .or_insert(0) += 1;
+        *self.turn.development_cards_bought.entry(card).or_insert(0) += 1;
+
+        Ok(vec![GameEvent::DevelopmentCardPurchased(color, Some(card))])
+    }
+
+    fn check_play_development_card(
+        &self,
+        color: PlayerColor,
+        play: DevelopmentCardPlay,
+    ) -> RulesResult<()> {
+        self.check_development_card_playable(color, play.card())?;
+
+        match play {
+            DevelopmentCardPlay::Knight |
+            DevelopmentCardPlay::Monopoly(_) => Ok(()),
+            DevelopmentCardPlay::RoadBuilding(first, second) => {
+                self.check_road_building(color, first, second)
+            }
+            DevelopmentCardPlay::YearOfPlenty(first, second) => {
+                if self.resource_bank.satisfies(&year_of_plenty(first, second)) {
+                    Ok(())
+                } else {
+                    Err(RulesError::BankExhausted)
+                }
+            }
+        }
+    }
+
+    fn check_development_card_playable(
+        &self,
+        color: PlayerColor,
+        card: DevelopmentCardType,
+    ) -> RulesResult<()> {
+        if self.turn.development_card_played {
+            return Err(RulesError::DevelopmentCardAlreadyPlayed);
+        }
+        // Only a knight may be played before rolling
+        if !self.turn.rolled && card != DevelopmentCardType::Knight {
+            return Err(RulesError::ActionNotAllowed(ActionKind::PlayDevelopmentCard));
+        }
+
+        // Cards bought this turn cannot be played until the next one
+        let player = self.player(color).expect("Player color was already validated!");
+        let held = player.development_cards.get(&card).cloned().unwrap_or(0);
+        let bought = self.turn
+            .development_cards_bought
+            .get(&card)
+            .cloned()
+            .unwrap_or(0);
+        if held <= bought {
+            return Err(RulesError::DevelopmentCardNotPlayable(card));
+        }
+
+        Ok(())
+    }
+
+    fn check_road_building(
+        &self,
+        color: PlayerColor,
+        first: InternalEdge,
+        second: Option<InternalEdge>,
+    ) -> RulesResult<()> {
+        self.check_road_placement(color, first)?;
+
+        // The second road may extend the first one
+        let mut after_first = self.board.clone();
+        after_first.place_road(first, color);
+        match second {
+            Some(second) => self.check_road_placement_on(&after_first, color, second),
+            None => {
+                let room_for_second = after_first.edges().into_iter().any(|edge| {
+                    self.check_road_placement_on(&after_first, color, edge).is_ok()
+                });
+                if room_for_second {
+                    Err(RulesError::SecondRoadRequired)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Every way `color` could play the development cards they are able to
+    /// play right now, before checking the choices each play makes.
+    fn development_card_plays(&self, color: PlayerColor) -> Vec<DevelopmentCardPlay> {
+        let playable = |progress| {
+            let card = DevelopmentCardType::Progress(progress);
+            self.check_development_card_playable(color, card).is_ok()
+        };
+        let mut plays = vec![DevelopmentCardPlay::Knight];
+
+        if playable(DevelopmentProgressType::Monopoly) {
+            plays.extend(ALL_RESOURCE_TYPES.iter().map(|&resource_type| {
+                DevelopmentCardPlay::Monopoly(resource_type)
+            }));
+        }
+        if playable(DevelopmentProgressType::YearOfPlenty) {
+            for (index, &first) in ALL_RESOURCE_TYPES.iter().enumerate() {
+                for &second in ALL_RESOURCE_TYPES[index..].iter() {
+                    plays.push(DevelopmentCardPlay::YearOfPlenty(first, second));
+                }
+            }
+        }
+        if playable(DevelopmentProgressType::RoadBuilding) {
+            plays.extend(self.road_building_plays(color));
+        }
+
+        plays
+    }
+
+    fn road_building_plays(&self, color: PlayerColor) -> Vec<DevelopmentCardPlay> {
+        let mut plays = Vec::new();
+        let firsts: Vec<InternalEdge> = self.board
+            .edges()
+            .into_iter()
+            .filter(|edge| self.check_road_placement(color, *edge).is_ok())
+            .collect();
+
+        // One board is reused for every first road, which is taken up again
+        // once its second roads are known
+        let mut after_first = self.board.clone();
+        for &first in firsts.iter() {
+            // The second road either could have gone first or extends the first
+            let (a, b) = first.endpoints();
+            let mut candidates = firsts.clone();
+            candidates.extend(self.board.edges_at(a));
+            candidates.extend(self.board.edges_at(b));
+            candidates.sort();
+            candidates.dedup();
+
+            after_first.place_road(first, color);
+            let seconds: Vec<InternalEdge> = candidates
+                .into_iter()
+                .filter(|edge| self.check_road_placement_on(&after_first, color, *edge).is_ok())
+                .collect();
+            after_first.roads.remove(&first);
+
+            if seconds.is_empty() {
+                plays.push(DevelopmentCardPlay::RoadBuilding(first, None));
+            }
+            // Two independent roads can be placed in either order, so only
+            // list them once.
+            for second in seconds {
+                if second > first || !firsts.contains(&second) {
+                    plays.push(DevelopmentCardPlay::RoadBuilding(first, Some(second)));
+                }
+            }
+        }
+
+        plays
+    }
+
+    fn play_development_card(
+        &mut self,
+        color: PlayerColor,
+        play: DevelopmentCardPlay,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_play_development_card(color, play)?;
+
+        let card = play.card();
+        self.turn.development_card_played = true;
+        *self.player_mut(color)
+            .development_cards
+            .get_mut(&card)
+            .expect("Playable cards are always held") -= 1;
+
+        let mut events = vec![GameEvent::DevelopmentCardPlayed(color, card)];
+        match play {
+            DevelopmentCardPlay::Knight => {
+                self.player_mut(color).knights_played += 1;
+                events.extend(self.update_largest_army());
+                events.extend(self.set_phase(GamePhase::MoveRobber));
+            }
+            DevelopmentCardPlay::RoadBuilding(first, second) => {
+                for edge in Some(first).into_iter().chain(second) {
+                    self.board.place_road(edge, color);
+                    events.push(GameEvent::RoadPlaced(color, edge));
+                }
+                events.extend(self.update_longest_road());
+            }
+            DevelopmentCardPlay::Monopoly(resource_type) => {
+                let mut collected = 0;
+                for player in self.players.iter_mut().filter(|player| player.color != color) {
+                    collected += player.resources[resource_type];
+                    player.resources[resource_type] = 0;
+                }
+                self.player_mut(color).resources[resource_type] += collected;
+                events.push(GameEvent::ResourcesMonopolized(color, resource_type, collected));
+            }
+            DevelopmentCardPlay::YearOfPlenty(first, second) => {
+                let taken = year_of_plenty(first, second);
+                self.take_from_bank(color, taken)?;
+                events.push(GameEvent::ResourcesTakenFromBank(color, taken));
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn check_maritime_trade(
+        &self,
+        color: PlayerColor,
+        offer: ResourceCollection,
+        receipt: ResourceCollection,
+    ) -> RulesResult<()> {
+        let rates = maritime_rates(&self.board, color);
+        if !is_valid_maritime_trade(&rates, &offer, &receipt) {
+            return Err(RulesError::InvalidTrade);
+        }
+        if !self.resource_bank.satisfies(&receipt) {
+            return Err(RulesError::BankExhausted);
+        }
+
+        self.check_affordable(color, offer)
+    }
+
+    /// The smallest trades `color` can make with the bank: one card taken for
+    /// a single resource at its best rate.
+    fn maritime_trades(&self, color: PlayerColor) -> Vec<PlayerAction> {
+        let rates = maritime_rates(&self.board, color);
+        let mut trades = Vec::new();
+
+        for &given in ALL_RESOURCE_TYPES.iter() {
+            let mut offer = ResourceCollection::default();
+            offer[given] = rates[&given];
+
+            for &taken in ALL_RESOURCE_TYPES.iter() {
+                let mut receipt = ResourceCollection::default();
+                receipt[taken] = 1;
+
+                if self.check_maritime_trade(color, offer, receipt).is_ok() {
+                    trades.push(PlayerAction::MaritimeTrade(offer, receipt));
+                }
+            }
+        }
+
+        trades
+    }
+
+    fn trade_with_bank(
+        &mut self,
+        color: PlayerColor,
+        offer: ResourceCollection,
+        receipt: ResourceCollection,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_maritime_trade(color, offer, receipt)?;
+
+        self.pay_to_bank(color, offer)?;
+        self.take_from_bank(color, receipt)?;
+
+        Ok(vec![GameEvent::TradedWithBank(color, offer, receipt)])
+    }
+
+    // Players may not give cards away or swap a resource for itself.
+    fn check_trade_terms(
+        &self,
+        offer: ResourceCollection,
+        request: ResourceCollection,
+    ) -> RulesResult<()> {
+        let overlapping = ALL_RESOURCE_TYPES.iter().any(|resource_type| {
+            offer[*resource_type] > 0 && request[*resource_type] > 0
+        });
+        if offer.magnitude() == 0 || request.magnitude() == 0 || overlapping {
+            return Err(RulesError::InvalidTrade);
+        }
+
+        Ok(())
+    }
+
+    fn find_trade_offer(&self, id: TradeOfferId) -> RulesResult<&TradeOffer> {
+        self.trades.get(id).ok_or(RulesError::UnknownTradeOffer(id))
+    }
+
+    fn offer_trade(
+        &mut self,
+        color: PlayerColor,
+        to: Option<PlayerColor>,
+        offer: ResourceCollection,
+        request: ResourceCollection,
+    ) -> RulesResult<Vec<GameEvent>> {
+        if let Some(to) = to {
+            if to == color || self.player(to).is_none() {
+                return Err(RulesError::UnknownPlayer(to));
+            }
+        }
+        self.check_trade_terms(offer, request)?;
+        self.check_affordable(color, offer)?;
+
+        let posted = self.trades.post(color, to, offer, request, None).clone();
+
+        Ok(vec![GameEvent::TradeOffered(posted)])
+    }
+
+    fn counter_trade(
+        &mut self,
+        color: PlayerColor,
+        id: TradeOfferId,
+        offer: ResourceCollection,
+        request: ResourceCollection,
+    ) -> RulesResult<Vec<GameEvent>> {
+        let proposer = {
+            let original = self.find_trade_offer(id)?;
+            if !original.is_open_to(color) {
+                return Err(RulesError::UnknownTradeOffer(id));
+            }
+            original.from()
+        };
+        self.check_trade_terms(offer, request)?;
+        self.check_affordable(color, offer)?;
+
+        let posted = self.trades
+            .post(color, Some(proposer), offer, request, Some(id))
+            .clone();
+
+        Ok(vec![GameEvent::TradeOffered(posted)])
+    }
+
+    fn has_offer_open_to(&self, color: PlayerColor) -> bool {
+        self.trades.offers().iter().any(|offer| offer.is_open_to(color))
+    }
+
+    fn check_respond_to_trade(
+        &self,
+        color: PlayerColor,
+        id: TradeOfferId,
+        response: TradeResponse,
+    ) -> RulesResult<()> {
+        let offer = self.find_trade_offer(id)?;
+        if !offer.is_open_to(color) {
+            return Err(RulesError::UnknownTradeOffer(id));
+        }
+        // Only the current player can confirm a trade, so they take up an
+        // offer with `ConfirmTrade` instead of accepting it
+        if color == self.current_player() && response == TradeResponse::Accept {
+            return Err(RulesError::ActionNotAllowed(ActionKind::RespondToTrade));
+        }
+
+        match response {
+            TradeResponse::Accept => self.check_affordable(color, *offer.request()),
+            TradeResponse::Reject => Ok(()),
+        }
+    }
+
+    fn respond_to_trade(
+        &mut self,
+        color: PlayerColor,
+        id: TradeOfferId,
+        response: TradeResponse,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_respond_to_trade(color, id, response)?;
+
+        self.trades.respond(id, color, response);
+
+        Ok(vec![GameEvent::TradeResponded(id, color, response)])
+    }
+
+    // The current player either confirms their own offer with a player who
+    // accepted it, or takes up an offer made directly to them. Both sides
+    // must still hold their cards, since they may have been spent since.
+    fn check_confirm_trade(
+        &self,
+        color: PlayerColor,
+        id: TradeOfferId,
+        partner: PlayerColor,
+    ) -> RulesResult<()> {
+        let offer = self.find_trade_offer(id)?;
+        let agreed = if offer.from() == color {
+            offer.is_open_to(partner) && offer.response(partner) == Some(TradeResponse::Accept)
+        } else {
+            offer.from() == partner && offer.to() == Some(color)
+        };
+        if !agreed {
+            return Err(RulesError::TradeNotAccepted(partner));
+        }
+
+        let accepter = if offer.from() == color { partner } else { color };
+        self.check_affordable(offer.from(), *offer.offer())?;
+        self.check_affordable(accepter, *offer.request())
+    }
+
+    fn trade_confirmations(&self, color: PlayerColor) -> Vec<PlayerAction> {
+        let mut confirmations = Vec::new();
+
+        for offer in self.trades.offers() {
+            for player in self.players.iter() {
+                if self.check_confirm_trade(color, offer.id(), player.color).is_ok() {
+                    confirmations.push(PlayerAction::ConfirmTrade(offer.id(), player.color));
+                }
+            }
+        }
+
+        confirmations
+    }
+
+    fn confirm_trade(
+        &mut self,
+        color: PlayerColor,
+        id: TradeOfferId,
+        partner: PlayerColor,
+    ) -> RulesResult<Vec<GameEvent>> {
+        self.check_confirm_trade(color, id, partner)?;
+
+        let offer = self.trades.remove(id).expect("Offer was already validated!");
+        let accepter = if offer.from() == color { partner } else { color };
+        let mut proposer_resources = self.player_mut(offer.from()).resources;
+        let mut accepter_resources = self.player_mut(accepter).resources;
+        proposer_resources.try_pay(*offer.offer())?;
+        accepter_resources.try_pay(*offer.request())?;
+        self.player_mut(offer.from()).resources = proposer_resources + *offer.request();
+        self.player_mut(accepter).resources = accepter_resources + *offer.offer();
+
+        Ok(vec![GameEvent::TradeExecuted(id, offer.from(), accepter)])
+    }
+
+    fn update_largest_army(&mut self) -> Vec<GameEvent> {
+        let armies: Vec<(PlayerColor, u32)> = self.players
+            .iter()
+            .map(|player| (player.color, player.knights_played))
+            .collect();
+
+        let holder = award_holder(&armies, self.largest_army, MINIMUM_LARGEST_ARMY);
+        if holder == self.largest_army {
+            return Vec::new();
+        }
+        self.largest_army = holder;
+
+        vec![GameEvent::LargestArmyChanged(holder)]
+    }
+
+    fn end_turn(&mut self) -> RulesResult<Vec<GameEvent>> {
+        let next_index = (self.current_player_index + 1) % self.players.len() as u32;
+
+        let mut events = Vec::new();
+        let expired = self.trades.clear();
+        if !expired.is_empty() {
+            events.push(GameEvent::TradeOffersExpired(expired));
+        }
+
+        // With five or more players everyone else may build after a turn
+        // ends, in seating order, before the next turn starts.
+        let ended = match self.phase {
+            GamePhase::SpecialBuilding(ended) => ended,
+            _ if self.board_shape() == BoardShape::Extended => {
+                let ended = self.current_player();
+                events.extend(self.set_phase(GamePhase::SpecialBuilding(ended)));
+                events.extend(self.start_special_building(next_index));
+                return Ok(events);
+            }
+            _ => {
+                events.extend(self.set_phase(GamePhase::PreRoll));
+                events.extend(self.start_turn(next_index));
+                return Ok(events);
+            }
+        };
+
+        if self.players[next_index as usize].color == ended {
+            let turn_index = (next_index + 1) % self.players.len() as u32;
+            events.extend(self.set_phase(GamePhase::PreRoll));
+            events.extend(self.start_turn(turn_index));
+        } else {
+            events.extend(self.start_special_building(next_index));
+        }
+
+        Ok(events)
+    }
+}
+
+pub const ROAD_COST: ResourceCollection = ResourceCollection::new(0, 1, 0, 0, 1);
+pub const SETTLEMENT_COST: ResourceCollection = ResourceCollection::new(0, 1, 1, 1, 1);
+pub const CITY_COST: ResourceCollection = ResourceCollection::new(3, 0, 2, 0, 0);
+pub const DEVELOPMENT_CARD_COST: ResourceCollection = ResourceCollection::new(1, 0, 1, 1, 0);
+
+fn check_pieces_remaining(
+    board: &Board,
+    color: PlayerColor,
+    building: BuildingType,
+) -> RulesResult<()> {
+    let placed = match building {
+        BuildingType::Road => board.roads_of(color).len(),
+        _ => board.buildings_of(color, building).len(),
+    };
+
+    if placed < building.count() {
+        Ok(())
+    } else {
+        Err(RulesError::NoPiecesRemaining(building))
+    }
+}
+
+fn year_of_plenty(first: ResourceType, second: ResourceType) -> ResourceCollection {
+    vec![first, second].into_iter().collect()
+}
+
+impl Player {
+    fn new(color: PlayerColor) -> Player {
+        Player {
+            color,
+            resources: ResourceCollection::default(),
+            development_cards: HashMap::new(),
+            knights_played: 0,
+        }
+    }
+
+    pub fn color(&self) -> PlayerColor {
+        self.color
+    }
+
+    pub fn resources(&self) -> &ResourceCollection {
+        &self.resources
+    }
+
+    pub fn development_cards(&self) -> &HashMap<DevelopmentCardType, u32> {
+        &self.development_cards
+    }
+
+    pub fn knights_played(&self) -> u32 {
+        self.knights_played
+    }
+
+    pub fn development_card_count(&self) -> u32 {
+        self.development_cards.values().sum()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DevelopmentCardType {
+    Knight,
+    Progress(DevelopmentProgressType),
+    VictoryPoint(DevelopmentVictoryPointType),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DevelopmentProgressType {
+    RoadBuilding,
+    Monopoly,
+    YearOfPlenty,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DevelopmentVictoryPointType {
+    Chapel,
+    Library,
+    GreatHall,
+    Market,
+    University,
+}
+
+impl DevelopmentCardType {
+    /// How many of this card the 5-6 player extension deck holds.
+    pub fn extended_count(self) -> usize {
+        match self {
+            DevelopmentCardType::Knight => 20,
+            DevelopmentCardType::Progress(_) => 3,
+            DevelopmentCardType::VictoryPoint(_) => self.count(),
+        }
+    }
+}
+
+impl GameResource for DevelopmentCardType {
+    fn count(self) -> usize {
+        match self {
+            DevelopmentCardType::Knight => 14,
+            DevelopmentCardType::Progress(DevelopmentProgressType::RoadBuilding) => 2,
+            DevelopmentCardType::Progress(DevelopmentProgressType::Monopoly) => 2,
+            DevelopmentCardType::Progress(DevelopmentProgressType::YearOfPlenty) => 2,
+            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Chapel) => 1,
+            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Library) => 1,
+            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::GreatHall) => 1,
+            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Market) => 1,
+            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::University) => 1,
+        }
+    }
+
+    fn all_variants() -> HashSet<DevelopmentCardType> {
+        let mut variants: HashSet<DevelopmentCardType> = HashSet::new();
+
+        variants.insert(DevelopmentCardType::Knight);
+        variants.insert(DevelopmentCardType::Progress(
+            DevelopmentProgressType::RoadBuilding,
+        ));
+        variants.insert(DevelopmentCardType::Progress(
+            DevelopmentProgressType::Monopoly,
+        ));
+        variants.insert(DevelopmentCardType::Progress(
+            DevelopmentProgressType::YearOfPlenty,
+        ));
+        variants.insert(DevelopmentCardType::VictoryPoint(
+            DevelopmentVictoryPointType::Chapel,
+        ));
+        variants.insert(DevelopmentCardType::VictoryPoint(
+            DevelopmentVictoryPointType::Library,
+        ));
+        variants.insert(DevelopmentCardType::VictoryPoint(
+            DevelopmentVictoryPointType::GreatHall,
+        ));
+        variants.insert(DevelopmentCardType::VictoryPoint(
+            DevelopmentVictoryPointType::Market,
+        ));
+        variants.insert(DevelopmentCardType::VictoryPoint(
+            DevelopmentVictoryPointType::University,
+        ));
+
+        variants
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Dice {
+    range: Range<u32>,
+}
+
+impl Dice {
+    fn new() -> Dice {
+        Dice { range: Range::new(1, 7) }
+    }
+
+    fn roll<R: Rng>(&self, rng: &mut R) -> u32 {
+        self.range.ind_sample(rng)
+    }
+}
+
+// The dice hold no state of their own, so saved games rebuild them
+impl Default for Dice {
+    fn default() -> Dice {
+        Dice::new()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ResourceType {
+    Ore,
+    Brick,
+    Grain,
+    Wool,
+    Lumber,
+}
+
+pub const ALL_RESOURCE_TYPES: [ResourceType; 5] = [
+    ResourceType::Ore,
+    ResourceType::Brick,
+    ResourceType::Grain,
+    ResourceType::Wool,
+    ResourceType::Lumber,
+];
+
+impl GameResource for ResourceType {
+    fn count(self) -> usize {
+        match self {
+            ResourceType::Ore => 19,
+            ResourceType::Brick => 19,
+            ResourceType::Grain => 19,
+            ResourceType::Wool => 19,
+            ResourceType::Lumber => 19,
+        }
+    }
+
+    fn all_variants() -> HashSet<ResourceType> {
+        let mut variants: HashSet<ResourceType> = HashSet::new();
+
+        variants.insert(ResourceType::Ore);
+        variants.insert(ResourceType::Brick);
+        variants.insert(ResourceType::Grain);
+        variants.insert(ResourceType::Wool);
+        variants.insert(ResourceType::Lumber);
+
+        variants
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ResourceCollection {
+    ore: u32,
+    brick: u32,
+    grain: u32,
+    wool: u32,
+    lumber: u32,
+}
+
+/// Collections compare as multisets: one is at least another when it holds at
+/// least as many of every resource, so `a >= b` exactly when `a.satisfies(&b)`.
+impl PartialOrd for ResourceCollection {
+    fn partial_cmp(&self, other: &ResourceCollection) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else if self.satisfies(other) {
+            Some(Ordering::Greater)
+        } else if other.satisfies(self) {
+            Some(Ordering::Less)
+        } else {
+            None
+        }
+    }
+}
+
+impl ResourceCollection {
+    pub const fn new(
+        ore: u32,
+        brick: u32,
+        grain: u32,
+        wool: u32,
+        lumber: u32,
+    ) -> ResourceCollection {
+        ResourceCollection {
+            ore,
+            brick,
+            grain,
+            wool,
+            lumber,
+        }
+    }
+
+    pub fn full_bank() -> ResourceCollection {
+        let mut bank = ResourceCollection::default();
+        for resource_type in ResourceType::all_variants() {
+            bank[resource_type] = resource_type.count() as u32;
+        }
+
+        bank
+    }
+
+    /// Every resource card in a game on a board of this shape.
+    pub fn supply(shape: BoardShape) -> ResourceCollection {
+        match shape {
+            BoardShape::Standard => ResourceCollection::full_bank(),
+            BoardShape::Extended => {
+                let size = EXTENDED_BANK_SIZE;
+                ResourceCollection::new(size, size, size, size, size)
+            }
+        }
+    }
+
+    pub fn satisfies(&self, other: &ResourceCollection) -> bool {
+        self.ore >= other.ore && self.brick >= other.brick && self.grain >= other.grain &&
+            self.wool >= other.wool && self.lumber >= other.lumber
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        self.ore + self.brick + self.grain + self.wool + self.lumber
+    }
+
+    /// The most of each resource that both collections hold.
+    pub fn min(self, other: ResourceCollection) -> ResourceCollection {
+        ALL_RESOURCE_TYPES
+            .iter()
+            .map(|&resource_type| (resource_type, self[resource_type].min(other[resource_type])))
+            .collect()
+    }
+
+    /// The least of each resource that covers both collections.
+    pub fn max(self, other: ResourceCollection) -> ResourceCollection {
+        ALL_RESOURCE_TYPES
+            .iter()
+            .map(|&resource_type| (resource_type, self[resource_type].max(other[resource_type])))
+            .collect()
+    }
+
+    /// The cards in this collection that aren't matched by a card in `other`.
+    pub fn difference(self, other: ResourceCollection) -> ResourceCollection {
+        self.saturating_sub(other)
+    }
+
+    /// Each resource type with its count, in the order of `ALL_RESOURCE_TYPES`.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceType, u32)> {
+        let collection = *self;
+
+        ALL_RESOURCE_TYPES
+            .iter()
+            .map(move |&resource_type| (resource_type, collection[resource_type]))
+    }
+
+    /// `None` if any count would overflow.
+    pub fn checked_add(self, other: ResourceCollection) -> Option<ResourceCollection> {
+        Some(ResourceCollection {
+            ore: self.ore.checked_add(other.ore)?,
+            brick: self.brick.checked_add(other.brick)?,
+            grain: self.grain.checked_add(other.grain)?,
+            wool: self.wool.checked_add(other.wool)?,
+            lumber: self.lumber.checked_add(other.lumber)?,
+        })
+    }
+
+    /// `None` if the sum would hold more of any resource than `cap`, such as
+    /// cards returned to a bank that would then hold more than its supply.
+    pub fn capped_add(
+        self,
+        other: ResourceCollection,
+        cap: ResourceCollection,
+    ) -> Option<ResourceCollection> {
+        self.checked_add(other).filter(|sum| cap.satisfies(sum))
+    }
+
+    /// `None` if `other` holds more of any resource than this collection.
+    pub fn checked_sub(self, other: ResourceCollection) -> Option<ResourceCollection> {
+        Some(ResourceCollection {
+            ore: self.ore.checked_sub(other.ore)?,
+            brick: self.brick.checked_sub(other.brick)?,
+            grain: self.grain.checked_sub(other.grain)?,
+            wool: self.wool.checked_sub(other.wool)?,
+            lumber: self.lumber.checked_sub(other.lumber)?,
+        })
+    }
+
+    pub fn saturating_add(self, other: ResourceCollection) -> ResourceCollection {
+        ResourceCollection {
+            ore: self.ore.saturating_add(other.ore),
+            brick: self.brick.saturating_add(other.brick),
+            grain: self.grain.saturating_add(other.grain),
+            wool: self.wool.saturating_add(other.wool),
+            lumber: self.lumber.saturating_add(other.lumber),
+        }
+    }
+
+    /// Takes away what it can of `other`, stopping each resource at zero.
+    pub fn saturating_sub(self, other: ResourceCollection) -> ResourceCollection {
+        ResourceCollection {
+            ore: self.ore.saturating_sub(other.ore),
+            brick: self.brick.saturating_sub(other.brick),
+            grain: self.grain.saturating_sub(other.grain),
+            wool: self.wool.saturating_sub(other.wool),
+            lumber: self.lumber.saturating_sub(other.lumber),
+        }
+    }
+
+    /// Removes `cost` from the collection. If it can't cover all of it, the
+    /// collection is left as it was and the missing cards are returned.
+    pub fn try_pay(&mut self, cost: ResourceCollection) -> Result<(), ResourceShortfall> {
+        match self.checked_sub(cost) {
+            Some(remaining) => {
+                *self = remaining;
+                Ok(())
+            }
+            None => Err(ResourceShortfall(cost.saturating_sub(*self))),
+        }
+    }
+
+    /// Picks one card uniformly at random, or `None` if the collection is empty.
+    pub fn random_resource<R: Rng>(&self, rng: &mut R) -> Option<ResourceType> {
+        if self.magnitude() == 0 {
+            return None;
+        }
+
+        let mut card = rng.gen_range(0, self.magnitude());
+        for &resource_type in ALL_RESOURCE_TYPES.iter() {
+            if card < self[resource_type] {
+                return Some(resource_type);
+            }
+            card -= self[resource_type];
+        }
+
+        unreachable!("Card index is always within the collection")
+    }
+
+    /// Every collection contained in this one with exactly `magnitude` cards.
+    pub fn sub_collections(&self, magnitude: u32) -> Vec<ResourceCollection> {
+        let mut collections = vec![(ResourceCollection::default(), magnitude)];
+
+        for (index, &resource_type) in ALL_RESOURCE_TYPES.iter().enumerate() {
+            let is_last = index == ALL_RESOURCE_TYPES.len() - 1;
+            collections = collections
+                .into_iter()
+                .flat_map(|(partial, remaining)| {
+                    let most = remaining.min(self[resource_type]);
+                    let least = if is_last { remaining } else { 0 };
+
+                    (least..(most + 1)).map(move |amount| {
+                        let mut next = partial;
+                        next[resource_type] = amount;
+                        (next, remaining - amount)
+                    })
+                })
+                .collect();
+        }
+
+        collections
+            .into_iter()
+            .map(|(collection, _)| collection)
+            .collect()
+    }
+}
+
+impl fmt::Debug for ResourceCollection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "(Ore: {}, Brick: {}, Grain: {}, Wool: {}, Lumber: {})",
+            self.ore,
+            self.brick,
+            self.grain,
+            self.wool,
+            self.lumber
+        )
+    }
+}
+
+impl Add for ResourceCollection {
+    type Output = ResourceCollection;
+
+    fn add(self, other: ResourceCollection) -> ResourceCollection {
+        self.checked_add(other).expect("Resource count overflowed!")
+    }
+}
+
+impl Sub for ResourceCollection {
+    type Output = ResourceCollection;
+
+    /// Panics, in release builds too, if `other` holds more of any resource.
+    /// Use `checked_sub` or `try_pay` when that can happen.
+    fn sub(self, other: ResourceCollection) -> ResourceCollection {
+        self.checked_sub(other).expect("Cannot remove more resources than the collection holds!")
+    }
+}
+
+impl Mul<u32> for ResourceCollection {
+    type Output = ResourceCollection;
+
+    fn mul(self, factor: u32) -> ResourceCollection {
+        self.iter()
+            .map(|(resource_type, count)| {
+                (resource_type, count.checked_mul(factor).expect("Resource count overflowed!"))
+            })
+            .collect()
+    }
+}
+
+/// Counts one card for every resource type in the iterator.
+impl FromIterator<ResourceType> for ResourceCollection {
+    fn from_iter<I: IntoIterator<Item = ResourceType>>(iter: I) -> ResourceCollection {
+        let mut collection = ResourceCollection::default();
+        for resource_type in iter {
+            collection[resource_type] += 1;
+        }
+
+        collection
+    }
+}
+
+impl FromIterator<(ResourceType, u32)> for ResourceCollection {
+    fn from_iter<I: IntoIterator<Item = (ResourceType, u32)>>(iter: I) -> ResourceCollection {
+        let mut collection = ResourceCollection::default();
+        for (resource_type, count) in iter {
+            collection[resource_type] += count;
+        }
+
+        collection
+    }
+}
+
+impl Index<ResourceType> for ResourceCollection {
+    type Output = u32;
+
+    fn index(&self, index: ResourceType) -> &u32 {
+        match index {
+            ResourceType::Ore => &self.ore,
+            ResourceType::Brick => &self.brick,
+            ResourceType::Grain => &self.grain,
+            ResourceType::Wool => &self.wool,
+            ResourceType::Lumber => &self.lumber,
+        }
+    }
+}
+
+impl IndexMut<ResourceType> for ResourceCollection {
+    fn index_mut(&mut self, index: ResourceType) -> &mut Self::Output {
+        match index {
+            ResourceType::Ore => &mut self.ore,
+            ResourceType::Brick => &mut self.brick,
+            ResourceType::Grain => &mut self.grain,
+            ResourceType::Wool => &mut self.wool,
+            ResourceType::Lumber => &mut self.lumber,
+        }
+    }
+}
+
+#[cfg(test)]
+mod resource_collection_tests {
+    use error::ResourceShortfall;
+    use game::{CITY_COST, ROAD_COST, ResourceCollection};
+    use game::ResourceType;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_creation() {
+        let resource_collection = ResourceCollection::new(3, 2, 1, 0, 4);
+
+        assert_eq!(resource_collection.ore, 3);
+        assert_eq!(resource_collection.brick, 2);
+        assert_eq!(resource_collection.grain, 1);
+        assert_eq!(resource_collection.wool, 0);
+        assert_eq!(resource_collection.lumber, 4);
+    }
+
+    #[test]
+    fn test_satisfies() {
+        let collection_a = ResourceCollection::new(3, 2, 1, 0, 4);
+        let does_satisfy = ResourceCollection::new(3, 1, 0, 0, 2);
+        let does_not_satisfy = ResourceCollection::new(2, 2, 2, 2, 2);
+
+        assert!(collection_a.satisfies(&does_satisfy));
+        assert!(!collection_a.satisfies(&does_not_satisfy));
+    }
+
+    #[test]
+    fn test_equality() {
+        let equal_a = ResourceCollection::new(1, 2, 1, 1, 1);
+        let equal_b = ResourceCollection::new(1, 2, 1, 1, 1);
+        let not_equal = ResourceCollection::new(1, 1, 1, 2, 1);
+
+        assert!(equal_a == equal_b);
+        assert!(equal_a != not_equal);
+        assert!(equal_b != not_equal);
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let collection = ResourceCollection::new(3, 0, 1, 0, 2);
+
+        assert_eq!(
+            collection.checked_add(ResourceCollection::new(1, 1, 0, 0, 0)),
+            Some(ResourceCollection::new(4, 1, 1, 0, 2))
+        );
+        assert_eq!(
+            collection.checked_add(ResourceCollection::new(0, u32::MAX, 0, 0, 0)),
+            Some(ResourceCollection::new(3, u32::MAX, 1, 0, 2))
+        );
+        assert_eq!(collection.checked_add(ResourceCollection::new(u32::MAX, 0, 0, 0, 0)), None);
+        assert_eq!(collection.checked_sub(CITY_COST), None);
+        assert_eq!(
+            collection.checked_sub(ResourceCollection::new(3, 0, 0, 0, 1)),
+            Some(ResourceCollection::new(0, 0, 1, 0, 1))
+        );
+    }
+
+    #[test]
+    fn test_capped_addition() {
+        let bank = ResourceCollection::new(18, 19, 0, 0, 0);
+        let supply = ResourceCollection::full_bank();
+
+        assert_eq!(
+            bank.capped_add(ResourceCollection::new(1, 0, 2, 0, 0), supply),
+            Some(ResourceCollection::new(19, 19, 2, 0, 0))
+        );
+        assert_eq!(bank.capped_add(ROAD_COST, supply), None);
+        assert_eq!(bank.capped_add(ResourceCollection::new(2, 0, 0, 0, 0), supply), None);
+    }
+
+    #[test]
+    fn test_saturating_arithmetic() {
+        let collection = ResourceCollection::new(3, 0, 1, 0, 2);
+
+        assert_eq!(collection.saturating_sub(CITY_COST), ResourceCollection::new(0, 0, 0, 0, 2));
+        assert_eq!(
+            collection.saturating_add(ResourceCollection::new(u32::MAX, 0, 0, 0, 1)),
+            ResourceCollection::new(u32::MAX, 0, 1, 0, 3)
+        );
+    }
+
+    #[test]
+    fn test_try_pay() {
+        let mut collection = ResourceCollection::new(3, 0, 1, 0, 2);
+
+        assert_eq!(
+            collection.try_pay(CITY_COST),
+            Err(ResourceShortfall(ResourceCollection::new(0, 0, 1, 0, 0)))
+        );
+        assert_eq!(collection, ResourceCollection::new(3, 0, 1, 0, 2));
+
+        collection[ResourceType::Grain] += 1;
+        assert_eq!(collection.try_pay(CITY_COST), Ok(()));
+        assert_eq!(collection, ResourceCollection::new(0, 0, 0, 0, 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_subtraction_never_wraps() {
+        let _ = ResourceCollection::new(0, 1, 0, 0, 0) - CITY_COST;
+    }
+
+    #[test]
+    fn test_comparison() {
+        let lower_collection = ResourceCollection::new(0, 1, 2, 3, 4);
+        let middle_collection = ResourceCollection::new(1, 2, 3, 4, 5);
+        let upper_collection = ResourceCollection::new(2, 3, 4, 5, 6);
+
+        let lower_middle_collection = ResourceCollection::new(0, 1, 2, 4, 5);
+        let middle_upper_collection = ResourceCollection::new(1, 2, 3, 5, 6);
+
+        // Normal orderings
+        assert!(lower_collection < middle_collection);
+        assert!(middle_collection < upper_collection);
+        assert!(upper_collection > lower_collection);
+
+        // Collections that share some counts are still ordered
+        assert!(lower_collection < lower_middle_collection);
+        assert!(lower_middle_collection < middle_collection);
+        assert!(middle_collection <= middle_upper_collection);
+        assert!(middle_upper_collection < upper_collection);
+        assert!(ResourceCollection::new(1, 0, 0, 0, 0) > ResourceCollection::default());
+        assert!(middle_collection >= middle_collection);
+
+        // Incomplete orderings, no order can be produced
+        let mixed_collection = ResourceCollection::new(1, 1, 2, 3, 4);
+        assert_eq!(mixed_collection.partial_cmp(&lower_middle_collection), None);
+        assert_eq!(lower_middle_collection.partial_cmp(&mixed_collection), None);
+        assert!(mixed_collection > lower_collection);
+        assert!(lower_collection != lower_middle_collection);
+        assert!(lower_middle_collection != middle_collection);
+        assert!(middle_collection != middle_upper_collection);
+        assert!(middle_upper_collection != upper_collection);
+    }
+
+    #[test]
+    fn test_addition() {
+        let collection_a = ResourceCollection::new(0, 0, 1, 1, 1);
+        let collection_b = ResourceCollection::new(2, 1, 0, 1, 2);
+        let result = ResourceCollection::new(2, 1, 1, 2, 3);
+
+        assert_eq!(collection_a + collection_b, result);
+    }
+
+    #[test]
+    fn test_subtraction() {
+        let collection_a = ResourceCollection::new(2, 1, 1, 2, 3);
+        let collection_b = ResourceCollection::new(2, 1, 0, 1, 2);
+        let result = ResourceCollection::new(0, 0, 1, 1, 1);
+
+        assert_eq!(collection_a - collection_b, result);
+    }
+
+    #[test]
+    fn test_comparison_matches_satisfies() {
+        let hands = [
+            ResourceCollection::new(0, 1, 1, 1, 1),
+            ResourceCollection::new(3, 0, 2, 0, 0),
+            ResourceCollection::new(3, 1, 2, 1, 1),
+            ResourceCollection::new(1, 0, 0, 0, 0),
+            ResourceCollection::default(),
+        ];
+
+        for first in hands.iter() {
+            for second in hands.iter() {
+                assert_eq!(first >= second, first.satisfies(second));
+                assert_eq!(first <= second, second.satisfies(first));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lattice_operations() {
+        let first = ResourceCollection::new(3, 0, 2, 1, 0);
+        let second = ResourceCollection::new(1, 1, 2, 0, 4);
+
+        assert_eq!(first.min(second), ResourceCollection::new(1, 0, 2, 0, 0));
+        assert_eq!(first.max(second), ResourceCollection::new(3, 1, 2, 1, 4));
+        assert_eq!(first.difference(second), ResourceCollection::new(2, 0, 0, 1, 0));
+        assert_eq!(second.difference(first), ResourceCollection::new(0, 1, 0, 0, 4));
+        assert!(first.min(second) <= first && first.min(second) <= second);
+        assert!(first.max(second) >= first && first.max(second) >= second);
+        assert_eq!(first.min(second) + first.difference(second), first);
+    }
+
+    #[test]
+    fn test_iteration() {
+        let collection = ResourceCollection::new(3, 0, 2, 1, 0);
+        let counts: Vec<(ResourceType, u32)> = collection.iter().collect();
+
+        assert_eq!(counts[0], (ResourceType::Ore, 3));
+        assert_eq!(counts[4], (ResourceType::Lumber, 0));
+        assert_eq!(collection.iter().map(|(_, count)| count).sum::<u32>(), 6);
+        assert_eq!(collection.iter().collect::<ResourceCollection>(), collection);
+
+        let cards = vec![ResourceType::Ore, ResourceType::Grain, ResourceType::Ore];
+        assert_eq!(
+            cards.into_iter().collect::<ResourceCollection>(),
+            ResourceCollection::new(2, 0, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_scalar_multiplication() {
+        assert_eq!(CITY_COST * 2, ResourceCollection::new(6, 0, 4, 0, 0));
+        assert_eq!(ROAD_COST * 3, ResourceCollection::new(0, 3, 0, 0, 3));
+    }
+
+    #[test]
+    fn test_random_resource() {
+        let mut rng = thread_rng();
+
+        assert_eq!(ResourceCollection::default().random_resource(&mut rng), None);
+        assert_eq!(
+            ResourceCollection::new(0, 0, 0, 2, 0).random_resource(&mut rng),
+            Some(ResourceType::Wool)
+        );
+
+        let collection = ResourceCollection::new(1, 0, 1, 0, 1);
+        for _ in 0..20 {
+            let resource_type = collection.random_resource(&mut rng).unwrap();
+            assert!(collection[resource_type] > 0);
+        }
+    }
+
+    #[test]
+    fn test_sub_collections() {
+        let collection = ResourceCollection::new(1, 2, 0, 0, 1);
+
+        let mut pairs = collection.sub_collections(2);
+        pairs.sort_by_key(|pair| format!("{:?}", pair));
+        assert_eq!(
+            pairs,
+            vec![
+                ResourceCollection::new(0, 1, 0, 0, 1),
+                ResourceCollection::new(0, 2, 0, 0, 0),
+                ResourceCollection::new(1, 0, 0, 0, 1),
+                ResourceCollection::new(1, 1, 0, 0, 0),
+            ]
+        );
+        assert_eq!(collection.sub_collections(4), vec![collection]);
+        assert!(collection.sub_collections(5).is_empty());
+        assert_eq!(
+            collection.sub_collections(0),
+            vec![ResourceCollection::default()]
+        );
+    }
+
+    #[test]
+    fn test_indexing() {
+        let collection = ResourceCollection::new(2, 3, 5, 7, 9);
 
         assert_eq!(collection[ResourceType::Ore], 2);
         assert_eq!(collection[ResourceType::Brick], 3);
@@ -386,19 +2355,1641 @@ mod resource_collection_tests {
     }
 
     #[test]
-    fn test_mutable_indexing() {
-        let mut collection = ResourceCollection::new(2, 3, 5, 7, 9);
+    fn test_mutable_indexing() {
+        let mut collection = ResourceCollection::new(2, 3, 5, 7, 9);
+
+        collection[ResourceType::Grain] -= 2;
+        collection[ResourceType::Ore] += 3;
+        collection[ResourceType::Lumber] /= 3;
+        collection[ResourceType::Wool] %= 3;
+        collection[ResourceType::Brick] = 0;
+
+        assert_eq!(collection[ResourceType::Ore], 5);
+        assert_eq!(collection[ResourceType::Brick], 0);
+        assert_eq!(collection[ResourceType::Grain], 3);
+        assert_eq!(collection[ResourceType::Wool], 1);
+        assert_eq!(collection[ResourceType::Lumber], 3);
+    }
+}
+
+/// Ways for tests anywhere in the crate to set up a game directly.
+#[cfg(test)]
+pub mod test_util {
+    use rand::Rng;
+    use game::{CatanGame, DevelopmentCardType, GameSettings, PlayerAction, PlayerColor,
+               ResourceCollection};
+    use rng::GameRng;
+
+    /// Applies a random legal action for the first seated player who has
+    /// one, or returns `None` once nobody can act.
+    pub fn play_random_action(
+        game: &mut CatanGame,
+        rng: &mut GameRng,
+    ) -> Option<(PlayerColor, PlayerAction)> {
+        let actor = game.players()
+            .iter()
+            .map(|player| player.color())
+            .find(|color| !game.legal_actions(*color).is_empty())?;
+
+        let action = rng.choose(&game.legal_actions(actor)).unwrap().clone();
+        if let Err(error) = game.apply(actor, action.clone()) {
+            panic!("Legal action {:?} was rejected: {:?}", action, error);
+        }
+
+        Some((actor, action))
+    }
+
+    /// A game between `colors` after up to `steps` random legal actions. The
+    /// game and the choice of actions both follow from `seed`.
+    pub fn played_game(colors: &[PlayerColor], seed: u64, steps: usize) -> CatanGame {
+        let settings = GameSettings { seed, ..GameSettings::default() };
+        let mut game = CatanGame::with_settings(colors, settings).unwrap();
+        let mut rng = GameRng::from_seed(seed);
+
+        for _ in 0..steps {
+            if play_random_action(&mut game, &mut rng).is_none() {
+                break;
+            }
+        }
+
+        game
+    }
+
+    /// Sets `color`'s hand, trading cards with the bank so that none are made
+    /// up or lost along the way.
+    pub fn give(game: &mut CatanGame, color: PlayerColor, resources: ResourceCollection) {
+        let held = game.player_mut(color).resources;
+        game.resource_bank = game.resource_bank + held - resources;
+        game.player_mut(color).resources = resources;
+    }
+
+    pub fn give_development_cards(
+        game: &mut CatanGame,
+        color: PlayerColor,
+        cards: &[(DevelopmentCardType, u32)],
+    ) {
+        game.player_mut(color).development_cards = cards.iter().cloned().collect();
+    }
+}
+
+#[cfg(test)]
+mod catan_game_tests {
+    use board::{Board, BoardShape, BuildingType, InternalCoord, InternalEdge};
+    use error::{ResourceShortfall, RulesError};
+    use game::{CatanGame, DevelopmentCardPlay, DevelopmentCardType, DevelopmentProgressType,
+               DevelopmentVictoryPointType, GameEvent, GameSettings, PlayerAction, PlayerColor,
+               ResourceCollection, ResourceType, DEVELOPMENT_CARD_COST, ROAD_COST,
+               SETTLEMENT_COST};
+    use game::test_util::{give, play_random_action, played_game};
+    use generator::{BoardConstraints, BoardLayout};
+    use map::MapFile;
+    use phase::{ActionKind, GamePhase, SetupRound, SetupStep};
+    use rng::GameRng;
+    use trade::{TradeOfferId, TradeResponse};
+    use std::collections::HashMap;
+
+    fn two_player_game() -> CatanGame {
+        CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue])
+    }
+
+    #[test]
+    fn test_new_game() {
+        let game = two_player_game();
+
+        assert_eq!(game.current_player(), PlayerColor::Red);
+        assert_eq!(game.players().len(), 2);
+        assert_eq!(*game.resource_bank(), ResourceCollection::new(19, 19, 19, 19, 19));
+    }
+
+    #[test]
+    fn test_turn_order_enforced() {
+        let mut game = two_player_game();
+
+        assert_eq!(
+            game.apply(PlayerColor::Blue, PlayerAction::RollDice),
+            Err(RulesError::NotYourTurn(PlayerColor::Blue))
+        );
+        assert_eq!(
+            game.apply(PlayerColor::White, PlayerAction::RollDice),
+            Err(RulesError::UnknownPlayer(PlayerColor::White))
+        );
+    }
+
+    #[test]
+    fn test_roll_then_end_turn() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::PreRoll;
+
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::EndTurn),
+            Err(RulesError::ActionNotAllowed(ActionKind::EndTurn))
+        );
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::RollDice).unwrap();
+        match events[0] {
+            GameEvent::DiceRolled(first, second) => {
+                assert!((1..=6).contains(&first));
+                assert!((1..=6).contains(&second));
+            }
+            ref other => panic!("Unexpected event {:?}", other),
+        }
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::RollDice),
+            Err(RulesError::ActionNotAllowed(ActionKind::RollDice))
+        );
+
+        // Nobody holds any cards, so a seven goes straight to the robber
+        if *game.phase() == GamePhase::MoveRobber {
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::MoveRobber(InternalCoord::new(2, -1, -1), None),
+            ).unwrap();
+        }
+        assert_eq!(*game.phase(), GamePhase::Main);
+
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::EndTurn),
+            Ok(vec![
+                GameEvent::PhaseChanged(GamePhase::PreRoll),
+                GameEvent::TurnStarted(PlayerColor::Blue),
+            ])
+        );
+        assert_eq!(game.current_player(), PlayerColor::Blue);
+    }
+
+    #[test]
+    fn test_setup_snake_draft() {
+        let mut game = CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::White]);
+        let placements = [
+            (PlayerColor::Red, InternalCoord::new(1, -1, 0)),
+            (PlayerColor::Blue, InternalCoord::new(3, -2, -1)),
+            (PlayerColor::White, InternalCoord::new(-1, 0, 1)),
+            (PlayerColor::White, InternalCoord::new(-2, 2, 0)),
+            (PlayerColor::Blue, InternalCoord::new(1, 2, -3)),
+            (PlayerColor::Red, InternalCoord::new(0, 1, -1)),
+        ];
+
+        for (index, &(color, location)) in placements.iter().enumerate() {
+            let round = if index < 3 {
+                SetupRound::First
+            } else {
+                SetupRound::Second
+            };
+            assert_eq!(*game.phase(), GamePhase::Setup(round, SetupStep::Settlement));
+            assert_eq!(game.current_player(), color);
+            assert_eq!(
+                game.allowed_actions(color),
+                vec![ActionKind::BuildSettlement]
+            );
+
+            game.apply(color, PlayerAction::BuildSettlement(location))
+                .unwrap();
+            assert_eq!(*game.phase(), GamePhase::Setup(round, SetupStep::Road(location)));
+            assert_eq!(game.current_player(), color);
+
+            let road = game.board().edges_at(location)[0];
+            let elsewhere = edge((1, 0, -1), (2, 0, -2));
+            assert_eq!(
+                game.apply(color, PlayerAction::BuildRoad(elsewhere)),
+                Err(RulesError::InvalidEdge(elsewhere))
+            );
+            game.apply(color, PlayerAction::BuildRoad(road)).unwrap();
+            assert_eq!(game.board().road_at(road), Some(color));
+        }
+
+        assert_eq!(*game.phase(), GamePhase::PreRoll);
+        assert_eq!(game.current_player(), PlayerColor::Red);
+
+        // Only the second settlement yields resources: (0, 1, -1) touches the
+        // desert, the fields (11) and the hills (3)
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(0, 1, 1, 0, 0)
+        );
+        assert_eq!(
+            *game.player(PlayerColor::Blue).unwrap().resources(),
+            ResourceCollection::new(0, 0, 2, 1, 0)
+        );
+        assert_eq!(
+            *game.player(PlayerColor::White).unwrap().resources(),
+            ResourceCollection::new(0, 2, 0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_discard_on_seven() {
+        let mut game = CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::White]);
+        give(&mut game, PlayerColor::Blue, ResourceCollection::new(3, 3, 3, 0, 0));
+        give(&mut game, PlayerColor::White, ResourceCollection::new(2, 2, 2, 1, 0));
+
+        let mut pending = game.pending_discards();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[&PlayerColor::Blue], 4);
+
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(1, 1, 1, 1, 5));
+        pending.insert(PlayerColor::Red, 4);
+        game.phase = GamePhase::Discard(pending);
+
+        assert_eq!(game.allowed_actions(PlayerColor::White), vec![]);
+        assert_eq!(
+            game.allowed_actions(PlayerColor::Blue),
+            vec![ActionKind::Discard]
+        );
+        assert_eq!(
+            game.apply(
+                PlayerColor::Blue,
+                PlayerAction::Discard(ResourceCollection::new(3, 0, 0, 0, 0)),
+            ),
+            Err(RulesError::WrongDiscardAmount(4))
+        );
+        assert_eq!(
+            game.apply(
+                PlayerColor::Blue,
+                PlayerAction::Discard(ResourceCollection::new(0, 0, 0, 4, 0)),
+            ),
+            Err(RulesError::InsufficientResources(
+                ResourceShortfall(ResourceCollection::new(0, 0, 0, 4, 0)),
+            ))
+        );
+
+        game.apply(
+            PlayerColor::Blue,
+            PlayerAction::Discard(ResourceCollection::new(2, 2, 0, 0, 0)),
+        ).unwrap();
+        let discarded = ResourceCollection::new(0, 0, 0, 0, 4);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::Discard(discarded)),
+            Ok(vec![
+                GameEvent::ResourcesDiscarded(PlayerColor::Red, discarded),
+                GameEvent::PhaseChanged(GamePhase::MoveRobber),
+            ])
+        );
+        assert_eq!(game.allowed_actions(PlayerColor::Red), vec![ActionKind::MoveRobber]);
+    }
+
+    #[test]
+    fn test_discard_rounds_down() {
+        let mut game = CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::White]);
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(2, 2, 2, 2, 1));
+        give(&mut game, PlayerColor::Blue, ResourceCollection::new(2, 2, 2, 2, 0));
+        give(&mut game, PlayerColor::White, ResourceCollection::new(7, 0, 0, 0, 0));
+
+        let pending = game.pending_discards();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[&PlayerColor::Red], 4);
+        assert_eq!(pending[&PlayerColor::Blue], 4);
+    }
+
+    #[test]
+    fn test_move_robber_and_steal() {
+        let mut game = CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::White]);
+        game.phase = GamePhase::MoveRobber;
+        game.turn.rolled = true;
+        let mountains = InternalCoord::new(2, -1, -1);
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        game.board.place_building(
+            InternalCoord::new(3, -2, -1),
+            PlayerColor::Blue,
+            BuildingType::Settlement,
+        );
+        game.board.place_building(
+            InternalCoord::new(2, 0, -2),
+            PlayerColor::White,
+            BuildingType::Settlement,
+        );
+        give(&mut game, PlayerColor::Blue, ResourceCollection::new(0, 0, 3, 0, 0));
+
+        // White has no cards and red cannot rob themselves
+        assert_eq!(game.steal_targets(PlayerColor::Red, mountains), vec![PlayerColor::Blue]);
+
+        let desert = InternalCoord::new(0, 0, 0);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::MoveRobber(desert, None)),
+            Err(RulesError::RobberMustMove)
+        );
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::MoveRobber(mountains, None)),
+            Err(RulesError::InvalidStealTarget(None))
+        );
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::MoveRobber(mountains, Some(PlayerColor::White)),
+            ),
+            Err(RulesError::InvalidStealTarget(Some(PlayerColor::White)))
+        );
+
+        let robber_moves = game.legal_actions(PlayerColor::Red)
+            .into_iter()
+            .filter(|action| match *action {
+                PlayerAction::MoveRobber(coordinate, _) => coordinate == mountains,
+                _ => false,
+            })
+            .count();
+        assert_eq!(robber_moves, 1);
+
+        let stolen = |resource_type| {
+            GameEvent::ResourceStolen(PlayerColor::Red, PlayerColor::Blue, resource_type)
+        };
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::MoveRobber(mountains, Some(PlayerColor::Blue)),
+            ),
+            Ok(vec![
+                GameEvent::RobberMoved(mountains),
+                stolen(Some(ResourceType::Grain)),
+                GameEvent::PhaseChanged(GamePhase::Main),
+            ])
+        );
+        let event = stolen(Some(ResourceType::Grain));
+        assert_eq!(event.redacted_for(PlayerColor::Red), event);
+        assert_eq!(event.redacted_for(PlayerColor::Blue), event);
+        assert_eq!(event.redacted_for(PlayerColor::White), stolen(None));
+        assert_eq!(game.board().robber, Some(mountains));
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(0, 0, 1, 0, 0)
+        );
+        assert_eq!(
+            *game.player(PlayerColor::Blue).unwrap().resources(),
+            ResourceCollection::new(0, 0, 2, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_victory_ends_game() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        let cities = [
+            InternalCoord::new(1, -1, 0),
+            InternalCoord::new(3, -2, -1),
+            InternalCoord::new(-1, 0, 1),
+            InternalCoord::new(-2, 2, 0),
+        ];
+        for &location in cities.iter() {
+            game.board.place_building(location, PlayerColor::Red, BuildingType::City);
+        }
+        game.board.place_building(
+            InternalCoord::new(1, 2, -3),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        game.board.place_road(edge((0, 1, -1), (0, 2, -2)), PlayerColor::Red);
+        give(&mut game, PlayerColor::Red, SETTLEMENT_COST);
+        assert_eq!(game.public_score(PlayerColor::Red), 9);
+
+        let location = InternalCoord::new(0, 1, -1);
+        let events = game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(location))
+            .unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::GameWon(PlayerColor::Red)));
+        assert_eq!(*game.phase(), GamePhase::GameOver(PlayerColor::Red));
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::EndTurn),
+            Err(RulesError::GameOver)
+        );
+    }
+
+    fn short_game() -> CatanGame {
+        let settings = GameSettings { victory_point_target: 3, ..GameSettings::default() };
+        let mut game = CatanGame::with_settings(&[PlayerColor::Red, PlayerColor::Blue], settings)
+            .unwrap();
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+
+        game
+    }
+
+    #[test]
+    fn test_hidden_victory_points() {
+        let mut game = two_player_game();
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::City,
+        );
+        game.player_mut(PlayerColor::Red).development_cards.insert(
+            DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Library),
+            1,
+        );
+        game.player_mut(PlayerColor::Red)
+            .development_cards
+            .insert(DevelopmentCardType::Knight, 2);
 
-        collection[ResourceType::Grain] -= 2;
-        collection[ResourceType::Ore] += 3;
-        collection[ResourceType::Lumber] /= 3;
-        collection[ResourceType::Wool] %= 3;
-        collection[ResourceType::Brick] = 0;
+        assert_eq!(game.public_score(PlayerColor::Red), 2);
+        assert_eq!(game.true_score(PlayerColor::Red), 3);
+        assert_eq!(game.true_score(PlayerColor::Blue), 0);
+        assert_eq!(game.settings().victory_point_target, 10);
+    }
 
-        assert_eq!(collection[ResourceType::Ore], 5);
-        assert_eq!(collection[ResourceType::Brick], 0);
-        assert_eq!(collection[ResourceType::Grain], 3);
-        assert_eq!(collection[ResourceType::Wool], 1);
-        assert_eq!(collection[ResourceType::Lumber], 3);
+    #[test]
+    fn test_win_with_victory_point_card() {
+        let mut game = short_game();
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::City,
+        );
+        give(&mut game, PlayerColor::Red, DEVELOPMENT_CARD_COST);
+        let library = DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Library);
+        game.development_deck = vec![library];
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::PurchaseDevelopmentCard).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::GameWon(PlayerColor::Red)));
+        assert_eq!(game.public_score(PlayerColor::Red), 2);
+    }
+
+    #[test]
+    fn test_only_current_player_wins() {
+        let mut game = short_game();
+        let settlements = [
+            InternalCoord::new(1, -1, 0),
+            InternalCoord::new(3, -2, -1),
+            InternalCoord::new(-1, 0, 1),
+        ];
+        for &location in settlements.iter() {
+            game.board.place_building(location, PlayerColor::Blue, BuildingType::Settlement);
+        }
+        give(&mut game, PlayerColor::Red, DEVELOPMENT_CARD_COST);
+        game.development_deck = vec![DevelopmentCardType::Knight];
+
+        game.apply(PlayerColor::Red, PlayerAction::PurchaseDevelopmentCard).unwrap();
+        assert_eq!(*game.phase(), GamePhase::Main);
+
+        // Blue wins as soon as their own turn starts
+        let events = game.apply(PlayerColor::Red, PlayerAction::EndTurn).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::GameWon(PlayerColor::Blue)));
+        assert_eq!(*game.phase(), GamePhase::GameOver(PlayerColor::Blue));
+    }
+
+    #[test]
+    fn test_maritime_trade() {
+        let mut game = short_game();
+        game.board.place_building(
+            InternalCoord::new(3, 1, -4),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(4, 2, 0, 0, 0));
+        game.resource_bank = ResourceCollection::new(15, 17, 0, 1, 19);
+
+        assert_eq!(
+            game.maritime_quote(PlayerColor::Red, ResourceCollection::new(4, 2, 0, 0, 0)),
+            2
+        );
+        assert_eq!(
+            game.maritime_quote(PlayerColor::Red, ResourceCollection::new(4, 2, 0, 0, 19)),
+            1
+        );
+
+        let ore = ResourceCollection::new(3, 0, 0, 0, 0);
+        let brick = ResourceCollection::new(0, 2, 0, 0, 0);
+        let grain = ResourceCollection::new(0, 0, 1, 0, 0);
+        let wool = ResourceCollection::new(0, 0, 0, 1, 0);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::MaritimeTrade(ore, wool)),
+            Err(RulesError::InvalidTrade)
+        );
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::MaritimeTrade(brick, grain)),
+            Err(RulesError::BankExhausted)
+        );
+
+        assert!(game.legal_actions(PlayerColor::Red).iter().any(|action| {
+            matches!(*action, PlayerAction::MaritimeTrade(offer, receipt)
+                if offer == brick && receipt == ResourceCollection::new(0, 0, 0, 0, 1))
+        }));
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::MaritimeTrade(brick, wool))
+            .unwrap();
+        assert_eq!(events, vec![GameEvent::TradedWithBank(PlayerColor::Red, brick, wool)]);
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(4, 0, 0, 1, 0)
+        );
+        assert_eq!(*game.resource_bank(), ResourceCollection::new(15, 19, 0, 0, 19));
+    }
+
+    fn trading_game() -> CatanGame {
+        let mut game = CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::White]);
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(2, 0, 0, 0, 0));
+        give(&mut game, PlayerColor::Blue, ResourceCollection::new(0, 1, 0, 0, 0));
+        give(&mut game, PlayerColor::White, ResourceCollection::new(0, 1, 0, 0, 1));
+
+        game
+    }
+
+    fn ore(count: u32) -> ResourceCollection {
+        ResourceCollection::new(count, 0, 0, 0, 0)
+    }
+
+    fn brick(count: u32) -> ResourceCollection {
+        ResourceCollection::new(0, count, 0, 0, 0)
+    }
+
+    #[test]
+    fn test_trade_with_accepter() {
+        let mut game = trading_game();
+        let reject = PlayerAction::RespondToTrade(TradeOfferId(0), TradeResponse::Reject);
+        assert_eq!(
+            game.apply(PlayerColor::Blue, reject),
+            Err(RulesError::NotYourTurn(PlayerColor::Blue))
+        );
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::OfferTrade(None, ore(1), ore(1))),
+            Err(RulesError::InvalidTrade)
+        );
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::OfferTrade(None, ore(3), brick(1))),
+            Err(RulesError::InsufficientResources(ResourceShortfall(ore(1))))
+        );
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::OfferTrade(None, ore(2), brick(1)))
+            .unwrap();
+        let id = match events[0] {
+            GameEvent::TradeOffered(ref offer) => offer.id(),
+            ref other => panic!("Unexpected event {:?}", other),
+        };
+        assert_eq!(game.allowed_actions(PlayerColor::Blue), vec![ActionKind::RespondToTrade]);
+
+        game.apply(PlayerColor::White, PlayerAction::RespondToTrade(id, TradeResponse::Reject))
+            .unwrap();
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::ConfirmTrade(id, PlayerColor::Blue)),
+            Err(RulesError::TradeNotAccepted(PlayerColor::Blue))
+        );
+        game.apply(PlayerColor::Blue, PlayerAction::RespondToTrade(id, TradeResponse::Accept))
+            .unwrap();
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::ConfirmTrade(id, PlayerColor::White)),
+            Err(RulesError::TradeNotAccepted(PlayerColor::White))
+        );
+        assert_eq!(
+            game.legal_actions(PlayerColor::Red)
+                .iter()
+                .filter(|action| matches!(**action, PlayerAction::ConfirmTrade(..)))
+                .count(),
+            1
+        );
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::ConfirmTrade(id, PlayerColor::Blue))
+            .unwrap();
+        assert_eq!(events, vec![GameEvent::TradeExecuted(id, PlayerColor::Red, PlayerColor::Blue)]);
+        assert_eq!(*game.player(PlayerColor::Red).unwrap().resources(), brick(1));
+        assert_eq!(*game.player(PlayerColor::Blue).unwrap().resources(), ore(2));
+        assert!(game.trade_offers().is_empty());
+    }
+
+    #[test]
+    fn test_counter_offer() {
+        let mut game = trading_game();
+        game.apply(
+            PlayerColor::Red,
+            PlayerAction::OfferTrade(Some(PlayerColor::Blue), ore(2), brick(1)),
+        ).unwrap();
+        let id = game.trade_offers()[0].id();
+
+        assert_eq!(
+            game.apply(PlayerColor::White, PlayerAction::CounterTrade(id, brick(1), ore(1))),
+            Err(RulesError::NotYourTurn(PlayerColor::White))
+        );
+        let events = game.apply(PlayerColor::Blue, PlayerAction::CounterTrade(id, brick(1), ore(1)))
+            .unwrap();
+        let counter = match events[0] {
+            GameEvent::TradeOffered(ref offer) => offer.clone(),
+            ref other => panic!("Unexpected event {:?}", other),
+        };
+        assert_eq!(counter.counter_to(), Some(id));
+        assert_eq!(counter.to(), Some(PlayerColor::Red));
+
+        game.apply(PlayerColor::Red, PlayerAction::ConfirmTrade(counter.id(), PlayerColor::Blue))
+            .unwrap();
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(1, 1, 0, 0, 0)
+        );
+        assert_eq!(*game.player(PlayerColor::Blue).unwrap().resources(), ore(1));
+        assert_eq!(game.trade_offers().len(), 1);
+    }
+
+    #[test]
+    fn test_current_player_answers_counter_offer() {
+        let mut game = trading_game();
+        game.apply(
+            PlayerColor::Red,
+            PlayerAction::OfferTrade(Some(PlayerColor::Blue), ore(2), brick(1)),
+        ).unwrap();
+        let id = game.trade_offers()[0].id();
+        assert!(!game.allowed_actions(PlayerColor::Red).contains(&ActionKind::RespondToTrade));
+
+        game.apply(PlayerColor::Blue, PlayerAction::CounterTrade(id, brick(1), ore(1))).unwrap();
+        let counter = game.trade_offers()[1].id();
+        assert!(game.allowed_actions(PlayerColor::Red).contains(&ActionKind::RespondToTrade));
+        let legal = game.legal_actions(PlayerColor::Red);
+        assert!(legal.contains(&PlayerAction::RespondToTrade(counter, TradeResponse::Reject)));
+        assert!(legal.contains(&PlayerAction::ConfirmTrade(counter, PlayerColor::Blue)));
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::RespondToTrade(counter, TradeResponse::Accept),
+            ),
+            Err(RulesError::ActionNotAllowed(ActionKind::RespondToTrade))
+        );
+
+        // Red counters Blue's counter, which Blue accepts and Red confirms
+        let events = game.apply(
+            PlayerColor::Red,
+            PlayerAction::CounterTrade(counter, ore(2), brick(1)),
+        ).unwrap();
+        assert!(matches!(events[0], GameEvent::TradeOffered(ref offer)
+            if offer.counter_to() == Some(counter) && offer.to() == Some(PlayerColor::Blue)));
+        let recounter = game.trade_offers()[2].id();
+        game.apply(
+            PlayerColor::Blue,
+            PlayerAction::RespondToTrade(recounter, TradeResponse::Accept),
+        ).unwrap();
+        game.apply(PlayerColor::Red, PlayerAction::ConfirmTrade(recounter, PlayerColor::Blue))
+            .unwrap();
+        assert_eq!(*game.player(PlayerColor::Red).unwrap().resources(), brick(1));
+
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::RespondToTrade(counter, TradeResponse::Reject),
+            ),
+            Ok(vec![GameEvent::TradeResponded(counter, PlayerColor::Red, TradeResponse::Reject)])
+        );
+    }
+
+    #[test]
+    fn test_trade_rechecks_holdings() {
+        let mut game = trading_game();
+        game.apply(PlayerColor::Red, PlayerAction::OfferTrade(None, ore(2), brick(1)))
+            .unwrap();
+        let id = game.trade_offers()[0].id();
+        game.apply(PlayerColor::Blue, PlayerAction::RespondToTrade(id, TradeResponse::Accept))
+            .unwrap();
+
+        give(&mut game, PlayerColor::Blue, ResourceCollection::default());
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::ConfirmTrade(id, PlayerColor::Blue)),
+            Err(RulesError::InsufficientResources(ResourceShortfall(brick(1))))
+        );
+    }
+
+    #[test]
+    fn test_trade_offers_expire() {
+        let mut game = trading_game();
+        game.apply(PlayerColor::Red, PlayerAction::OfferTrade(None, ore(2), brick(1)))
+            .unwrap();
+        let id = game.trade_offers()[0].id();
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::EndTurn).unwrap();
+        assert_eq!(events[0], GameEvent::TradeOffersExpired(vec![id]));
+        assert!(game.trade_offers().is_empty());
+        assert_eq!(
+            game.apply(PlayerColor::White, PlayerAction::RespondToTrade(id, TradeResponse::Accept)),
+            Err(RulesError::NotYourTurn(PlayerColor::White))
+        );
+    }
+
+    fn place_path(game: &mut CatanGame, color: PlayerColor, path: &[(i32, i32, i32)]) {
+        for pair in path.windows(2) {
+            game.board.place_road(edge(pair[0], pair[1]), color);
+        }
+    }
+
+    fn give_knights(game: &mut CatanGame, color: PlayerColor, knights: u32) {
+        game.player_mut(color)
+            .development_cards
+            .insert(DevelopmentCardType::Knight, knights);
+    }
+
+    #[test]
+    fn test_play_knight_before_rolling() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::PreRoll;
+        give_knights(&mut game, PlayerColor::Red, 1);
+        let knight = PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight);
+
+        assert!(game.legal_actions(PlayerColor::Red).iter().any(|action| {
+            matches!(*action, PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight))
+        }));
+
+        let events = game.apply(PlayerColor::Red, knight).unwrap();
+        assert_eq!(
+            events[0],
+            GameEvent::DevelopmentCardPlayed(PlayerColor::Red, DevelopmentCardType::Knight)
+        );
+        assert_eq!(*game.phase(), GamePhase::MoveRobber);
+        assert_eq!(game.player(PlayerColor::Red).unwrap().knights_played(), 1);
+
+        let mountains = InternalCoord::new(2, -1, -1);
+        game.apply(PlayerColor::Red, PlayerAction::MoveRobber(mountains, None))
+            .unwrap();
+        assert_eq!(game.board().robber, Some(mountains));
+        assert_eq!(*game.phase(), GamePhase::PreRoll);
+    }
+
+    #[test]
+    fn test_one_development_card_per_turn() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+        give_knights(&mut game, PlayerColor::Red, 2);
+        let knight = PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight);
+
+        game.apply(PlayerColor::Red, knight.clone()).unwrap();
+        game.apply(
+            PlayerColor::Red,
+            PlayerAction::MoveRobber(InternalCoord::new(2, -1, -1), None),
+        ).unwrap();
+        assert_eq!(*game.phase(), GamePhase::Main);
+        assert_eq!(
+            game.apply(PlayerColor::Red, knight.clone()),
+            Err(RulesError::DevelopmentCardAlreadyPlayed)
+        );
+
+        // The limit resets when the next turn starts
+        game.start_turn(0);
+        game.phase = GamePhase::PreRoll;
+        assert!(game.apply(PlayerColor::Red, knight).is_ok());
+    }
+
+    #[test]
+    fn test_cannot_play_card_bought_this_turn() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+        give(&mut game, PlayerColor::Red, DEVELOPMENT_CARD_COST);
+        let knight = PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight);
+        game.development_deck = vec![DevelopmentCardType::Knight];
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::PurchaseDevelopmentCard).unwrap();
+        let purchased = |card| GameEvent::DevelopmentCardPurchased(PlayerColor::Red, card);
+        assert_eq!(events, vec![purchased(Some(DevelopmentCardType::Knight))]);
+        assert_eq!(events[0].redacted_for(PlayerColor::Red), events[0]);
+        assert_eq!(events[0].redacted_for(PlayerColor::Blue), purchased(None));
+        assert!(game.bought_this_turn(PlayerColor::Red, DevelopmentCardType::Knight));
+        assert_eq!(
+            game.apply(PlayerColor::Red, knight.clone()),
+            Err(RulesError::DevelopmentCardNotPlayable(DevelopmentCardType::Knight))
+        );
+
+        game.start_turn(0);
+        assert!(game.apply(PlayerColor::Red, knight).is_ok());
+    }
+
+    #[test]
+    fn test_development_deck() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+        assert_eq!(game.development_deck_size(), 25);
+        let mut counts = HashMap::new();
+        for card in game.development_deck.iter() {
+            *counts.entry(*card).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&DevelopmentCardType::Knight], 14);
+
+        game.development_deck.truncate(1);
+        let cost = DEVELOPMENT_CARD_COST;
+        give(&mut game, PlayerColor::Red, cost + cost);
+        game.apply(PlayerColor::Red, PlayerAction::PurchaseDevelopmentCard).unwrap();
+        assert_eq!(game.development_deck_size(), 0);
+        assert_eq!(game.development_card_counts()[&PlayerColor::Red], 1);
+        assert_eq!(game.development_card_counts()[&PlayerColor::Blue], 0);
+
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::PurchaseDevelopmentCard),
+            Err(RulesError::DevelopmentDeckEmpty)
+        );
+        assert_eq!(*game.player(PlayerColor::Red).unwrap().resources(), cost);
+        assert!(!game.legal_actions(PlayerColor::Red).iter().any(|action| {
+            matches!(*action, PlayerAction::PurchaseDevelopmentCard)
+        }));
+    }
+
+    #[test]
+    fn test_largest_army_award() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::PreRoll;
+        give_knights(&mut game, PlayerColor::Red, 1);
+        give_knights(&mut game, PlayerColor::Blue, 1);
+        game.player_mut(PlayerColor::Red).knights_played = 2;
+        game.player_mut(PlayerColor::Blue).knights_played = 3;
+        game.largest_army = Some(PlayerColor::Blue);
+        let knight = PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight);
+
+        // Drawing level with the holder does not take the card
+        let events = game.apply(PlayerColor::Red, knight.clone()).unwrap();
+        assert!(!events.iter().any(|event| matches!(*event, GameEvent::LargestArmyChanged(_))));
+        assert_eq!(game.largest_army_holder(), Some(PlayerColor::Blue));
+        assert_eq!(game.public_score(PlayerColor::Blue), 2);
+
+        game.start_turn(0);
+        game.phase = GamePhase::PreRoll;
+        give_knights(&mut game, PlayerColor::Red, 1);
+        let events = game.apply(PlayerColor::Red, knight).unwrap();
+        assert!(events.contains(&GameEvent::LargestArmyChanged(Some(PlayerColor::Red))));
+        assert_eq!(game.public_score(PlayerColor::Red), 2);
+        assert_eq!(game.public_score(PlayerColor::Blue), 0);
+    }
+
+    #[test]
+    fn test_largest_army_needs_three_knights() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::PreRoll;
+        give_knights(&mut game, PlayerColor::Red, 1);
+        game.player_mut(PlayerColor::Red).knights_played = 1;
+
+        game.apply(
+            PlayerColor::Red,
+            PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight),
+        ).unwrap();
+        assert_eq!(game.largest_army_holder(), None);
+
+        game.start_turn(0);
+        game.phase = GamePhase::PreRoll;
+        give_knights(&mut game, PlayerColor::Red, 1);
+        let events = game.apply(
+            PlayerColor::Red,
+            PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight),
+        ).unwrap();
+        assert!(events.contains(&GameEvent::LargestArmyChanged(Some(PlayerColor::Red))));
+    }
+
+    fn progress_game(progress: DevelopmentProgressType) -> CatanGame {
+        let mut game = CatanGame::new(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::White]);
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+        game.player_mut(PlayerColor::Red)
+            .development_cards
+            .insert(DevelopmentCardType::Progress(progress), 1);
+
+        game
+    }
+
+    #[test]
+    fn test_progress_cards_wait_for_roll() {
+        let mut game = progress_game(DevelopmentProgressType::Monopoly);
+        game.phase = GamePhase::PreRoll;
+        game.turn.rolled = false;
+
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Monopoly(ResourceType::Ore)),
+            ),
+            Err(RulesError::ActionNotAllowed(ActionKind::PlayDevelopmentCard))
+        );
+    }
+
+    #[test]
+    fn test_road_building() {
+        let mut game = progress_game(DevelopmentProgressType::RoadBuilding);
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        let first = edge((1, -1, 0), (2, -2, 0));
+        let second = edge((2, -2, 0), (3, -2, -1));
+
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::RoadBuilding(first, None)),
+            ),
+            Err(RulesError::SecondRoadRequired)
+        );
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::PlayDevelopmentCard(
+                    DevelopmentCardPlay::RoadBuilding(second, Some(first)),
+                ),
+            ),
+            Err(RulesError::NotConnected)
+        );
+        assert!(game.legal_actions(PlayerColor::Red).iter().any(|action| {
+            matches!(
+                *action,
+                PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::RoadBuilding(a, Some(b)))
+                    if a == first && b == second
+            )
+        }));
+
+        let road_building = DevelopmentCardPlay::RoadBuilding(first, Some(second));
+        let events = game.apply(PlayerColor::Red, PlayerAction::PlayDevelopmentCard(road_building))
+            .unwrap();
+        assert!(events.contains(&GameEvent::RoadPlaced(PlayerColor::Red, second)));
+        assert_eq!(game.board().roads_of(PlayerColor::Red).len(), 2);
+        assert_eq!(game.player(PlayerColor::Red).unwrap().resources().magnitude(), 0);
+    }
+
+    #[test]
+    fn test_monopoly() {
+        let mut game = progress_game(DevelopmentProgressType::Monopoly);
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(0, 0, 1, 0, 0));
+        give(&mut game, PlayerColor::Blue, ResourceCollection::new(1, 0, 3, 0, 0));
+        give(&mut game, PlayerColor::White, ResourceCollection::new(0, 0, 2, 0, 1));
+
+        let events = game.apply(
+            PlayerColor::Red,
+            PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Monopoly(ResourceType::Grain)),
+        ).unwrap();
+        assert!(events.contains(
+            &GameEvent::ResourcesMonopolized(PlayerColor::Red, ResourceType::Grain, 5),
+        ));
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(0, 0, 6, 0, 0)
+        );
+        assert_eq!(
+            *game.player(PlayerColor::Blue).unwrap().resources(),
+            ResourceCollection::new(1, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            *game.player(PlayerColor::White).unwrap().resources(),
+            ResourceCollection::new(0, 0, 0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_year_of_plenty_limited_by_bank() {
+        let mut game = progress_game(DevelopmentProgressType::YearOfPlenty);
+        game.resource_bank = ResourceCollection::new(1, 0, 4, 0, 0);
+
+        assert_eq!(
+            game.apply(
+                PlayerColor::Red,
+                PlayerAction::PlayDevelopmentCard(
+                    DevelopmentCardPlay::YearOfPlenty(ResourceType::Ore, ResourceType::Ore),
+                ),
+            ),
+            Err(RulesError::BankExhausted)
+        );
+        assert!(!game.legal_actions(PlayerColor::Red).iter().any(|action| {
+            matches!(
+                *action,
+                PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::YearOfPlenty(
+                    ResourceType::Brick,
+                    _,
+                ))
+            )
+        }));
+
+        game.apply(
+            PlayerColor::Red,
+            PlayerAction::PlayDevelopmentCard(
+                DevelopmentCardPlay::YearOfPlenty(ResourceType::Ore, ResourceType::Grain),
+            ),
+        ).unwrap();
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(1, 0, 1, 0, 0)
+        );
+        assert_eq!(*game.resource_bank(), ResourceCollection::new(0, 0, 3, 0, 0));
+    }
+
+    #[test]
+    fn test_longest_road_award() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        place_path(
+            &mut game,
+            PlayerColor::Red,
+            &[(-1, 0, 1), (-2, 0, 2), (-2, -1, 3), (-1, -2, 3), (-1, -3, 4)],
+        );
+        place_path(
+            &mut game,
+            PlayerColor::Blue,
+            &[(1, -1, 0), (1, 0, -1), (0, 1, -1), (-1, 1, 0), (-1, 0, 1)],
+        );
+        give(&mut game, PlayerColor::Red, ROAD_COST);
+        let road = ROAD_COST;
+        give(&mut game, PlayerColor::Blue, road + road);
+
+        let events = game.apply(
+            PlayerColor::Red,
+            PlayerAction::BuildRoad(edge((-1, -3, 4), (0, -4, 4))),
+        ).unwrap();
+        assert!(events.contains(&GameEvent::LongestRoadChanged(Some(PlayerColor::Red))));
+        assert_eq!(game.longest_road_holder(), Some(PlayerColor::Red));
+        assert_eq!(game.public_score(PlayerColor::Red), 2);
+
+        // Matching the holder's length is not enough to take the award
+        game.current_player_index = 1;
+        let events = game.apply(
+            PlayerColor::Blue,
+            PlayerAction::BuildRoad(edge((-1, 0, 1), (0, -1, 1))),
+        ).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(game.longest_road_holder(), Some(PlayerColor::Red));
+
+        let events = game.apply(
+            PlayerColor::Blue,
+            PlayerAction::BuildRoad(edge((0, -1, 1), (1, -1, 0))),
+        ).unwrap();
+        assert!(events.contains(&GameEvent::LongestRoadChanged(Some(PlayerColor::Blue))));
+        assert_eq!(game.public_score(PlayerColor::Red), 0);
+        assert_eq!(game.public_score(PlayerColor::Blue), 2);
+    }
+
+    #[test]
+    fn test_longest_road_broken_by_settlement() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        place_path(
+            &mut game,
+            PlayerColor::Red,
+            &[(-1, 0, 1), (-2, 0, 2), (-2, -1, 3), (-1, -2, 3), (-1, -3, 4), (0, -4, 4)],
+        );
+        game.longest_road = Some(PlayerColor::Red);
+        game.board.place_road(edge((-3, -1, 4), (-2, -1, 3)), PlayerColor::Blue);
+        game.current_player_index = 1;
+        give(&mut game, PlayerColor::Blue, SETTLEMENT_COST);
+
+        let events = game.apply(
+            PlayerColor::Blue,
+            PlayerAction::BuildSettlement(InternalCoord::new(-2, -1, 3)),
+        ).unwrap();
+        assert!(events.contains(&GameEvent::LongestRoadChanged(None)));
+        assert_eq!(game.longest_road_holder(), None);
+    }
+
+    fn edge(a: (i32, i32, i32), b: (i32, i32, i32)) -> InternalEdge {
+        InternalEdge::new(
+            InternalCoord::new(a.0, a.1, a.2),
+            InternalCoord::new(b.0, b.1, b.2),
+        )
+    }
+
+    #[test]
+    fn test_build_settlement() {
+        let mut game = two_player_game();
+        let location = InternalCoord::new(1, -1, 0);
+        game.phase = GamePhase::Main;
+
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(location)),
+            Err(RulesError::NotConnected)
+        );
+
+        game.board.place_road(edge((1, -1, 0), (2, -2, 0)), PlayerColor::Red);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(location)),
+            Err(RulesError::InsufficientResources(ResourceShortfall(SETTLEMENT_COST)))
+        );
+
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(0, 2, 2, 2, 2));
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(location)),
+            Ok(vec![
+                GameEvent::BuildingPlaced(PlayerColor::Red, BuildingType::Settlement, location),
+            ])
+        );
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(0, 1, 1, 1, 1)
+        );
+        assert_eq!(*game.resource_bank(), ResourceCollection::new(19, 18, 18, 18, 18));
+
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(location)),
+            Err(RulesError::LocationOccupied(location))
+        );
+        let neighbor = InternalCoord::new(2, -2, 0);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(neighbor)),
+            Err(RulesError::DistanceRuleViolated(neighbor))
+        );
+        let hex_center = InternalCoord::new(2, -1, -1);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(hex_center)),
+            Err(RulesError::InvalidLocation(hex_center))
+        );
+    }
+
+    #[test]
+    fn test_build_road() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        game.board.place_building(
+            InternalCoord::new(3, -2, -1),
+            PlayerColor::Blue,
+            BuildingType::Settlement,
+        );
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(0, 3, 0, 0, 3));
+
+        let detached = edge((1, 0, -1), (2, 0, -2));
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildRoad(detached)),
+            Err(RulesError::NotConnected)
+        );
+
+        let first = edge((1, -1, 0), (2, -2, 0));
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildRoad(first)),
+            Ok(vec![GameEvent::RoadPlaced(PlayerColor::Red, first)])
+        );
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildRoad(first)),
+            Err(RulesError::EdgeOccupied(first))
+        );
+
+        let second = edge((2, -2, 0), (3, -2, -1));
+        game.apply(PlayerColor::Red, PlayerAction::BuildRoad(second))
+            .unwrap();
+
+        // Blue's settlement at (3, -2, -1) cuts the road off
+        let blocked = edge((3, -2, -1), (3, -1, -2));
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildRoad(blocked)),
+            Err(RulesError::NotConnected)
+        );
+        assert_eq!(
+            *game.player(PlayerColor::Red).unwrap().resources(),
+            ResourceCollection::new(0, 1, 0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn test_build_city() {
+        let mut game = two_player_game();
+        let location = InternalCoord::new(1, -1, 0);
+        game.phase = GamePhase::Main;
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(3, 0, 2, 0, 0));
+
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildCity(location)),
+            Err(RulesError::InvalidLocation(location))
+        );
+
+        game.board.place_building(location, PlayerColor::Red, BuildingType::Settlement);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::BuildCity(location)),
+            Ok(vec![GameEvent::BuildingPlaced(PlayerColor::Red, BuildingType::City, location)])
+        );
+        assert_eq!(
+            game.board().building_at(location),
+            Some((PlayerColor::Red, BuildingType::City))
+        );
+    }
+
+    #[test]
+    fn test_production_respects_bank() {
+        let mut game = two_player_game();
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::City,
+        );
+        game.board.place_building(
+            InternalCoord::new(3, -2, -1),
+            PlayerColor::Blue,
+            BuildingType::Settlement,
+        );
+        game.resource_bank[ResourceType::Ore] = 2;
+
+        assert!(game.production(12).is_empty());
+        assert!(game.produce_resources(12).is_empty());
+        assert_eq!(game.resource_bank()[ResourceType::Ore], 2);
+
+        // Only blue is owed brick for a six, so blue takes what is left
+        game.resource_bank[ResourceType::Brick] = 0;
+        assert!(game.production(6).is_empty());
+        game.resource_bank[ResourceType::Brick] = 1;
+        assert_eq!(
+            game.produce_resources(6),
+            vec![
+                GameEvent::ResourcesProduced(
+                    PlayerColor::Blue,
+                    ResourceCollection::new(0, 1, 0, 0, 0),
+                ),
+            ]
+        );
+        assert_eq!(game.resource_bank()[ResourceType::Brick], 0);
+    }
+
+    #[test]
+    fn test_bank_never_exceeds_supply() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+        game.board.place_road(edge((1, -1, 0), (2, -2, 0)), PlayerColor::Red);
+
+        // Cards that didn't come from the bank have nowhere to go back to
+        game.player_mut(PlayerColor::Red).resources = ROAD_COST;
+        let road = PlayerAction::BuildRoad(edge((1, -1, 0), (1, 0, -1)));
+        assert_eq!(game.apply(PlayerColor::Red, road.clone()), Err(RulesError::BankSupplyExceeded));
+        assert_eq!(*game.player(PlayerColor::Red).unwrap().resources(), ROAD_COST);
+        assert_eq!(*game.resource_bank(), ResourceCollection::full_bank());
+
+        game.player_mut(PlayerColor::Red).resources = ResourceCollection::default();
+        give(&mut game, PlayerColor::Red, ROAD_COST);
+        assert!(game.apply(PlayerColor::Red, road).is_ok());
+        assert_eq!(*game.resource_bank(), ResourceCollection::full_bank());
+    }
+
+    #[test]
+    fn test_production() {
+        let mut game = two_player_game();
+        // Touches the mountains (12), forest (2) and desert hexes
+        game.board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::City,
+        );
+        game.board.place_building(
+            InternalCoord::new(3, -2, -1),
+            PlayerColor::Blue,
+            BuildingType::Settlement,
+        );
+
+        let mut events = game.produce_resources(12);
+        events.sort_by_key(|event| format!("{:?}", event));
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::ResourcesProduced(
+                    PlayerColor::Blue,
+                    ResourceCollection::new(1, 0, 0, 0, 0),
+                ),
+                GameEvent::ResourcesProduced(
+                    PlayerColor::Red,
+                    ResourceCollection::new(2, 0, 0, 0, 0),
+                ),
+            ]
+        );
+        assert_eq!(game.resource_bank()[ResourceType::Ore], 16);
+        assert!(game.produce_resources(7).is_empty());
+    }
+
+    #[test]
+    fn test_legal_actions_during_setup() {
+        let mut game = two_player_game();
+
+        let actions = game.legal_actions(PlayerColor::Red);
+        assert_eq!(actions.len(), 54);
+        assert!(game.legal_actions(PlayerColor::Blue).is_empty());
+
+        let location = InternalCoord::new(1, -1, 0);
+        game.apply(PlayerColor::Red, PlayerAction::BuildSettlement(location))
+            .unwrap();
+        let roads: Vec<_> = game.legal_actions(PlayerColor::Red)
+            .into_iter()
+            .map(|action| match action {
+                PlayerAction::BuildRoad(edge) => edge,
+                other => panic!("Unexpected action {:?}", other),
+            })
+            .collect();
+        let mut expected = game.board().edges_at(location);
+        expected.sort();
+        assert_eq!(roads, expected);
+    }
+
+    #[test]
+    fn test_legal_actions_in_main_phase() {
+        let mut game = two_player_game();
+        game.phase = GamePhase::Main;
+        let location = InternalCoord::new(1, -1, 0);
+        game.board.place_building(location, PlayerColor::Red, BuildingType::Settlement);
+
+        let actions = game.legal_actions(PlayerColor::Red);
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(actions[0], PlayerAction::EndTurn));
+
+        give(&mut game, PlayerColor::Red, ResourceCollection::new(3, 1, 2, 1, 1));
+        let mut counts = HashMap::new();
+        for action in game.legal_actions(PlayerColor::Red) {
+            *counts.entry(action.kind()).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&ActionKind::BuildRoad], 3);
+        assert_eq!(counts[&ActionKind::BuildCity], 1);
+        assert_eq!(counts[&ActionKind::PurchaseDevelopmentCard], 1);
+        assert_eq!(counts[&ActionKind::EndTurn], 1);
+        assert!(!counts.contains_key(&ActionKind::BuildSettlement));
+    }
+
+    fn two_player_playout(seed: u64, steps: usize) -> CatanGame {
+        played_game(&[PlayerColor::Red, PlayerColor::Blue], seed, steps)
+    }
+
+    #[test]
+    fn test_seeded_games_replay() {
+        let played = two_player_playout(1234, 300);
+
+        let mut replayed = two_player_playout(1234, 0);
+        for entry in played.history().entries() {
+            replayed.apply(entry.color, entry.action.clone()).unwrap();
+        }
+
+        assert_eq!(replayed.development_deck, played.development_deck);
+        assert_eq!(replayed.phase(), played.phase());
+        for player in played.players() {
+            let other = replayed.player(player.color()).unwrap();
+            assert_eq!(other.resources(), player.resources());
+            assert_eq!(other.development_cards(), player.development_cards());
+        }
+        assert_eq!(replayed.board().roads, played.board().roads);
+    }
+
+    #[test]
+    fn test_seed_changes_game() {
+        let first = CatanGame::with_settings(
+            &[PlayerColor::Red, PlayerColor::Blue],
+            GameSettings { seed: 1, ..GameSettings::default() },
+        ).unwrap();
+        let second = CatanGame::with_settings(
+            &[PlayerColor::Red, PlayerColor::Blue],
+            GameSettings { seed: 2, ..GameSettings::default() },
+        ).unwrap();
+
+        assert!(first.development_deck != second.development_deck);
+    }
+
+    #[test]
+    fn test_game_on_map() {
+        let mut map = MapFile::from_board(&Board::balanced_start());
+        map.hexes.retain(|hex| hex.position != [0, 0, 0]);
+        map.robber = None;
+        let json = ::serde_json::to_string(&map).unwrap();
+
+        let map: MapFile = ::serde_json::from_str(&json).unwrap();
+        let settings = GameSettings {
+            seed: 17,
+            board: BoardLayout::Map(map.clone()),
+            ..GameSettings::default()
+        };
+        let mut game = CatanGame::with_settings(&[PlayerColor::Red, PlayerColor::Blue], settings)
+            .unwrap();
+        assert_eq!(game.board().resource_tiles().len(), 18);
+        assert_eq!(game.board().robber, None);
+
+        let mut rng = GameRng::from_seed(17);
+        while game.phase().is_setup() {
+            play_random_action(&mut game, &mut rng).unwrap();
+        }
+        assert_eq!(game.board().roads.len(), 4);
+        let replayed = CatanGame::replay(game.history()).unwrap();
+        assert_eq!(replayed.to_json().unwrap(), game.to_json().unwrap());
+
+        let mut broken = map;
+        broken.robber = Some([0, 0, 0]);
+        let settings = GameSettings { board: BoardLayout::Map(broken), ..GameSettings::default() };
+        assert!(matches!(
+            CatanGame::with_settings(&[PlayerColor::Red, PlayerColor::Blue], settings),
+            Err(RulesError::InvalidMap(_))
+        ));
+    }
+
+    #[test]
+    fn test_impossible_board_constraints() {
+        let constraints = BoardConstraints {
+            max_pip_imbalance: Some(0),
+            max_attempts: 50,
+            ..BoardConstraints::default()
+        };
+        let settings = GameSettings {
+            seed: 1,
+            board: BoardLayout::Random(constraints),
+            ..GameSettings::default()
+        };
+
+        let result = CatanGame::with_settings(&[PlayerColor::Red, PlayerColor::Blue], settings);
+        assert_eq!(result.unwrap_err(), RulesError::UnsatisfiableBoardConstraints);
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let settings = GameSettings { seed: 40, ..GameSettings::default() };
+        let mut game = CatanGame::with_settings(&[PlayerColor::Red, PlayerColor::Blue], settings)
+            .unwrap();
+        assert_eq!(game.undo(), Err(RulesError::NothingToUndo));
+        assert_eq!(game.redo(), Err(RulesError::NothingToRedo));
+
+        let settlement = game.legal_actions(PlayerColor::Red)[0].clone();
+        game.apply(PlayerColor::Red, settlement).unwrap();
+        let road = game.legal_actions(PlayerColor::Red)[0].clone();
+        game.apply(PlayerColor::Red, road.clone()).unwrap();
+        let after_road = game.to_json().unwrap();
+
+        assert!(game.can_undo());
+        game.undo().unwrap();
+        assert_eq!(game.current_player(), PlayerColor::Red);
+        assert!(game.board().roads.is_empty());
+        assert!(game.can_redo());
+
+        game.undo().unwrap();
+        assert!(game.board().buildings_of(PlayerColor::Red, BuildingType::Settlement).is_empty());
+        assert!(!game.can_undo());
+
+        game.redo().unwrap();
+        assert_eq!(game.board().buildings_of(PlayerColor::Red, BuildingType::Settlement).len(), 1);
+        assert!(game.board().roads.is_empty());
+        let events = game.redo().unwrap();
+        assert!(matches!(events[0], GameEvent::RoadPlaced(PlayerColor::Red, _)));
+        assert_eq!(game.to_json().unwrap(), after_road);
+        assert!(!game.can_redo());
+    }
+
+    #[test]
+    fn test_new_action_clears_redo() {
+        let mut game = two_player_playout(41, 3);
+        game.undo().unwrap();
+        assert!(game.can_redo());
+
+        let action = game.legal_actions(game.current_player())[0].clone();
+        game.apply(game.current_player(), action).unwrap();
+        assert!(!game.can_redo());
+        assert_eq!(game.redo(), Err(RulesError::NothingToRedo));
+    }
+
+    #[test]
+    fn test_undo_stops_at_revealed_outcomes() {
+        let mut game = two_player_playout(42, 300);
+        let last_roll = game.history()
+            .entries()
+            .iter()
+            .rposition(|entry| entry.action == PlayerAction::RollDice)
+            .unwrap();
+
+        while game.history().len() > last_roll + 1 {
+            game.undo().unwrap();
+        }
+        let state = game.to_json().unwrap();
+
+        assert!(!game.can_undo());
+        assert_eq!(game.undo(), Err(RulesError::OutcomeRevealed));
+        assert_eq!(game.to_json().unwrap(), state);
+    }
+
+    fn six_player_game() -> CatanGame {
+        let mut game = CatanGame::with_settings(
+            &[
+                PlayerColor::Red,
+                PlayerColor::White,
+                PlayerColor::Orange,
+                PlayerColor::Blue,
+                PlayerColor::Green,
+                PlayerColor::Brown,
+            ],
+            GameSettings { seed: 16, ..GameSettings::default() },
+        ).unwrap();
+        game.phase = GamePhase::Main;
+        game.turn.rolled = true;
+
+        game
+    }
+
+    #[test]
+    fn test_six_player_supplies() {
+        let game = six_player_game();
+
+        assert_eq!(game.board_shape(), BoardShape::Extended);
+        assert_eq!(game.board().resource_tiles().len(), 30);
+        assert_eq!(*game.resource_bank(), ResourceCollection::new(24, 24, 24, 24, 24));
+        assert_eq!(game.development_deck_size(), 34);
+        let knights = game.development_deck
+            .iter()
+            .filter(|&&card| card == DevelopmentCardType::Knight)
+            .count();
+        assert_eq!(knights, 20);
+
+        assert_eq!(two_player_game().board_shape(), BoardShape::Standard);
+    }
+
+    #[test]
+    fn test_special_building_phase() {
+        let mut game = six_player_game();
+
+        let events = game.apply(PlayerColor::Red, PlayerAction::EndTurn).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::PhaseChanged(GamePhase::SpecialBuilding(PlayerColor::Red)),
+                GameEvent::SpecialBuildingStarted(PlayerColor::White),
+            ]
+        );
+        assert_eq!(game.current_player(), PlayerColor::White);
+        assert_eq!(
+            game.apply(PlayerColor::Red, PlayerAction::EndTurn),
+            Err(RulesError::NotYourTurn(PlayerColor::Red))
+        );
+
+        // Building and buying are allowed, trading and playing cards are not
+        give(&mut game, PlayerColor::White, DEVELOPMENT_CARD_COST + ore(4));
+        game.development_deck = vec![DevelopmentCardType::Knight];
+        game.apply(PlayerColor::White, PlayerAction::PurchaseDevelopmentCard)
+            .unwrap();
+        assert_eq!(
+            game.apply(PlayerColor::White, PlayerAction::MaritimeTrade(ore(4), brick(1))),
+            Err(RulesError::ActionNotAllowed(ActionKind::TradeResources))
+        );
+        assert_eq!(
+            game.apply(
+                PlayerColor::White,
+                PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight),
+            ),
+            Err(RulesError::ActionNotAllowed(ActionKind::PlayDevelopmentCard))
+        );
+
+        let builders = [
+            PlayerColor::White,
+            PlayerColor::Orange,
+            PlayerColor::Blue,
+            PlayerColor::Green,
+        ];
+        for &color in builders.iter() {
+            game.apply(color, PlayerAction::EndTurn).unwrap();
+            assert_eq!(*game.phase(), GamePhase::SpecialBuilding(PlayerColor::Red));
+        }
+
+        // The Special Building Phase ends with the player before the one
+        // whose turn it was, and play moves on as usual
+        let events = game.apply(PlayerColor::Brown, PlayerAction::EndTurn).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::PhaseChanged(GamePhase::PreRoll),
+                GameEvent::TurnStarted(PlayerColor::White),
+            ]
+        );
+
+        // The knight bought while building out of turn may be played now
+        game.apply(
+            PlayerColor::White,
+            PlayerAction::PlayDevelopmentCard(DevelopmentCardPlay::Knight),
+        ).unwrap();
+    }
+
+    #[test]
+    fn test_no_win_during_special_building() {
+        let mut game = six_player_game();
+        game.settings.victory_point_target = 3;
+        let settlements = [InternalCoord::new(1, -1, 0), InternalCoord::new(3, -2, -1)];
+        for &location in settlements.iter() {
+            game.board.place_building(location, PlayerColor::Orange, BuildingType::Settlement);
+        }
+        game.apply(PlayerColor::Red, PlayerAction::EndTurn).unwrap();
+        game.apply(PlayerColor::White, PlayerAction::EndTurn).unwrap();
+
+        give(&mut game, PlayerColor::Orange, DEVELOPMENT_CARD_COST);
+        let library = DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Library);
+        game.development_deck = vec![library];
+        let events = game.apply(PlayerColor::Orange, PlayerAction::PurchaseDevelopmentCard)
+            .unwrap();
+        assert!(!events.contains(&GameEvent::GameWon(PlayerColor::Orange)));
+
+        for &color in [PlayerColor::Orange, PlayerColor::Blue, PlayerColor::Green].iter() {
+            game.apply(color, PlayerAction::EndTurn).unwrap();
+        }
+        let events = game.apply(PlayerColor::Brown, PlayerAction::EndTurn).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::TurnStarted(PlayerColor::White)));
+
+        // Orange has to wait for their own turn
+        game.phase = GamePhase::Main;
+        game.apply(PlayerColor::White, PlayerAction::EndTurn).unwrap();
+        for &color in [PlayerColor::Orange, PlayerColor::Blue, PlayerColor::Green].iter() {
+            game.apply(color, PlayerAction::EndTurn).unwrap();
+        }
+        game.apply(PlayerColor::Brown, PlayerAction::EndTurn).unwrap();
+        let events = game.apply(PlayerColor::Red, PlayerAction::EndTurn).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::GameWon(PlayerColor::Orange)));
+    }
+
+    #[test]
+    fn test_six_player_legal_actions_apply() {
+        let mut game = six_player_game();
+        game.phase = GamePhase::Setup(SetupRound::First, SetupStep::Settlement);
+        let mut rng = GameRng::from_seed(16);
+
+        for _ in 0..300 {
+            if play_random_action(&mut game, &mut rng).is_none() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_legal_actions_always_apply() {
+        // Every action the playout picks comes from `legal_actions`, and it
+        // panics if one is rejected
+        for seed in 0..5 {
+            played_game(&[PlayerColor::Red, PlayerColor::Blue, PlayerColor::Orange], seed, 400);
+        }
     }
 }
@@ -0,0 +1,362 @@
+use std::collections::HashSet;
+use rand::Rng;
+use board::{Board, BoardShape, HarborType, ResourceTileType, RollToken};
+use common::GameResource;
+use error::{RulesError, RulesResult};
+use map::MapFile;
+
+/// Fairness rules a randomly generated board has to follow.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BoardConstraints {
+    /// Keep 6 and 8 tokens off neighboring hexes.
+    pub separate_high_probability: bool,
+    /// The most hexes of one terrain allowed to form a connected group.
+    pub max_resource_cluster: Option<usize>,
+    /// The largest allowed gap between the most and least productive
+    /// resources, counted in token pips.
+    pub max_pip_imbalance: Option<u32>,
+    /// How many layouts to try before giving up.
+    pub max_attempts: u32,
+}
+
+impl Default for BoardConstraints {
+    fn default() -> BoardConstraints {
+        BoardConstraints {
+            separate_high_probability: true,
+            max_resource_cluster: Some(2),
+            max_pip_imbalance: Some(6),
+            max_attempts: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum BoardLayout {
+    Balanced,
+    Random(BoardConstraints),
+    /// A designed board, such as one loaded from a map file. The whole map
+    /// is kept so that saved games and logs can rebuild it.
+    Map(MapFile),
+}
+
+impl BoardLayout {
+    /// Maps are used as they are, whatever `shape` the player count asks for.
+    pub fn build<R: Rng>(&self, rng: &mut R, shape: BoardShape) -> RulesResult<Board> {
+        match *self {
+            BoardLayout::Balanced => Ok(Board::balanced(shape)),
+            BoardLayout::Random(ref constraints) => {
+                random_board(rng, shape, constraints)
+                    .ok_or(RulesError::UnsatisfiableBoardConstraints)
+            }
+            BoardLayout::Map(ref map) => {
+                map.to_board().map_err(|error| RulesError::InvalidMap(error.to_string()))
+            }
+        }
+    }
+}
+
+/// Shuffles terrain, roll tokens and harbors onto a board of the given shape
+/// until the result satisfies `constraints`, or gives up after
+/// `constraints.max_attempts` tries.
+pub fn random_board<R: Rng>(
+    rng: &mut R,
+    shape: BoardShape,
+    constraints: &BoardConstraints,
+) -> Option<Board> {
+    let adjacency = tile_adjacency(shape);
+
+    for _ in 0..constraints.max_attempts {
+        let terrain = ResourceTileType::shuffled_collection(rng, |tile| shape.tile_count(tile));
+        if let Some(max_cluster) = constraints.max_resource_cluster {
+            if largest_cluster(&terrain, &adjacency) > max_cluster {
+                continue;
+            }
+        }
+
+        let mut tokens = RollToken::shuffled_collection(rng, |token| shape.token_count(token))
+            .into_iter();
+        let tiles: Vec<(ResourceTileType, Option<RollToken>)> = terrain
+            .iter()
+            .map(|&tile_type| match tile_type {
+                ResourceTileType::Desert => (tile_type, None),
+                _ => (tile_type, tokens.next()),
+            })
+            .collect();
+        if constraints.separate_high_probability && high_probability_adjacent(&tiles, &adjacency) {
+            continue;
+        }
+        if let Some(max_imbalance) = constraints.max_pip_imbalance {
+            if pip_imbalance(&tiles) > max_imbalance {
+                continue;
+            }
+        }
+
+        let harbors = HarborType::shuffled_collection(rng, |harbor| shape.harbor_count(harbor));
+
+        return Some(Board::with_layout(shape, &tiles, &harbors));
+    }
+
+    None
+}
+
+/// Up to `count` random boards, none of them a rotation or reflection of
+/// another, as told apart by `Board::canonical_hash`. Stops early if a board
+/// can't be generated or `constraints.max_attempts` boards in a row repeat
+/// ones already found.
+pub fn distinct_random_boards<R: Rng>(
+    rng: &mut R,
+    shape: BoardShape,
+    constraints: &BoardConstraints,
+    count: usize,
+) -> Vec<Board> {
+    let mut seen = HashSet::new();
+    let mut boards = Vec::new();
+    let mut repeats = 0;
+
+    while boards.len() < count && repeats < constraints.max_attempts {
+        let board = match random_board(rng, shape, constraints) {
+            Some(board) => board,
+            None => break,
+        };
+
+        if seen.insert(board.canonical_hash()) {
+            boards.push(board);
+            repeats = 0;
+        } else {
+            repeats += 1;
+        }
+    }
+
+    boards
+}
+
+/// For each tile position, the positions of the hexes next to it.
+fn tile_adjacency(shape: BoardShape) -> Vec<Vec<usize>> {
+    let locations = shape.tile_locations();
+
+    locations
+        .iter()
+        .map(|coordinate| {
+            let neighbors = coordinate.diagonal_neighbors();
+            locations
+                .iter()
+                .enumerate()
+                .filter(|&(_, other)| neighbors.contains(other))
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .collect()
+}
+
+fn largest_cluster(terrain: &[ResourceTileType], adjacency: &[Vec<usize>]) -> usize {
+    let mut visited = vec![false; terrain.len()];
+    let mut largest = 0;
+
+    for start in 0..terrain.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let mut size = 0;
+        let mut stack = vec![start];
+        while let Some(index) = stack.pop() {
+            size += 1;
+            for &neighbor in adjacency[index].iter() {
+                if !visited[neighbor] && terrain[neighbor] == terrain[start] {
+                    visited[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+
+    largest
+}
+
+fn high_probability_adjacent(
+    tiles: &[(ResourceTileType, Option<RollToken>)],
+    adjacency: &[Vec<usize>],
+) -> bool {
+    let high_prob = |index: usize| tiles[index].1.is_some_and(|token| token.high_prob());
+
+    (0..tiles.len()).any(|index| {
+        high_prob(index) && adjacency[index].iter().any(|&neighbor| high_prob(neighbor))
+    })
+}
+
+/// The gap in total pips between the most and least productive resources.
+fn pip_imbalance(tiles: &[(ResourceTileType, Option<RollToken>)]) -> u32 {
+    let pips: Vec<u32> = ResourceTileType::all_variants()
+        .into_iter()
+        .filter(|tile_type| *tile_type != ResourceTileType::Desert)
+        .map(|tile_type| {
+            tiles
+                .iter()
+                .filter(|&&(other, _)| other == tile_type)
+                .filter_map(|&(_, token)| token.map(RollToken::pips))
+                .sum()
+        })
+        .collect();
+
+    pips.iter().max().unwrap_or(&0) - pips.iter().min().unwrap_or(&0)
+}
+
+#[cfg(test)]
+mod generator_tests {
+    use board::{Board, BoardShape, HarborType, InternalTileType, ResourceTileType, RollToken};
+    use common::GameResource;
+    use generator::{BoardConstraints, BoardLayout, distinct_random_boards, random_board};
+    use rng::GameRng;
+    use std::collections::{HashMap, HashSet};
+
+    fn tile_counts(board: &Board) -> HashMap<ResourceTileType, usize> {
+        let mut counts = HashMap::new();
+        for tile in board.tiles.values() {
+            if let InternalTileType::ResourceTile(tile_type) = *tile {
+                *counts.entry(tile_type).or_insert(0) += 1;
+            }
+        }
+
+        counts
+    }
+
+    #[test]
+    fn test_random_board_uses_full_supply() {
+        let constraints = BoardConstraints::default();
+        let board = random_board(&mut GameRng::from_seed(3), BoardShape::Standard, &constraints)
+            .unwrap();
+
+        let counts = tile_counts(&board);
+        for tile_type in ResourceTileType::all_variants() {
+            assert_eq!(counts[&tile_type], tile_type.count());
+        }
+        assert_eq!(board.roll_tokens.len(), 18);
+        assert_eq!(board.building_tiles().len(), 54);
+        assert_eq!(board.edges().len(), 72);
+
+        let desert = board.robber.unwrap();
+        assert_eq!(
+            board.tiles[&desert],
+            InternalTileType::ResourceTile(ResourceTileType::Desert)
+        );
+        assert!(!board.roll_tokens.contains_key(&desert));
+
+        let generic_harbors = board
+            .harbors
+            .values()
+            .filter(|&&(harbor_type, _)| harbor_type == HarborType::All)
+            .count();
+        assert_eq!(generic_harbors, 4);
+    }
+
+    #[test]
+    fn test_random_board_constraints() {
+        let constraints = BoardConstraints {
+            max_pip_imbalance: Some(5),
+            ..BoardConstraints::default()
+        };
+
+        for seed in 0..5 {
+            let mut rng = GameRng::from_seed(seed);
+            let board = random_board(&mut rng, BoardShape::Standard, &constraints).unwrap();
+
+            for (coordinate, token) in board.roll_tokens.iter() {
+                let InternalTileType::ResourceTile(tile_type) = board.tiles[coordinate] else {
+                    panic!("Roll token on a building tile");
+                };
+                let mut same_terrain = 0;
+                for neighbor in coordinate.diagonal_neighbors() {
+                    if let Some(other) = board.roll_tokens.get(&neighbor) {
+                        assert!(!(token.high_prob() && other.high_prob()));
+                    }
+                    let terrain = board.tiles.get(&neighbor);
+                    if terrain == Some(&InternalTileType::ResourceTile(tile_type)) {
+                        same_terrain += 1;
+                    }
+                }
+                // A cluster of at most two means no hex touches more than one of its kind
+                assert!(same_terrain <= 1);
+            }
+
+            let mut pips: HashMap<ResourceTileType, u32> = HashMap::new();
+            for (coordinate, token) in board.roll_tokens.iter() {
+                if let InternalTileType::ResourceTile(tile_type) = board.tiles[coordinate] {
+                    *pips.entry(tile_type).or_insert(0) += token.pips();
+                }
+            }
+            let most = pips.values().max().unwrap();
+            let least = pips.values().min().unwrap();
+            assert!(most - least <= 5);
+        }
+    }
+
+    #[test]
+    fn test_random_extended_board() {
+        let constraints = BoardConstraints::default();
+        let board = random_board(&mut GameRng::from_seed(5), BoardShape::Extended, &constraints)
+            .unwrap();
+
+        let counts = tile_counts(&board);
+        for tile_type in ResourceTileType::all_variants() {
+            assert_eq!(counts[&tile_type], BoardShape::Extended.tile_count(tile_type));
+        }
+        assert_eq!(board.roll_tokens.len(), 28);
+        assert_eq!(board.harbors.len(), 11);
+        assert_eq!(board.building_tiles().len(), 80);
+    }
+
+    #[test]
+    fn test_impossible_constraints() {
+        let constraints = BoardConstraints {
+            max_pip_imbalance: Some(0),
+            max_attempts: 50,
+            ..BoardConstraints::default()
+        };
+
+        let mut rng = GameRng::from_seed(1);
+        assert!(random_board(&mut rng, BoardShape::Standard, &constraints).is_none());
+    }
+
+    #[test]
+    fn test_same_seed_same_board() {
+        let layout = BoardLayout::Random(BoardConstraints::default());
+        let first = layout.build(&mut GameRng::from_seed(11), BoardShape::Standard).unwrap();
+        let second = layout.build(&mut GameRng::from_seed(11), BoardShape::Standard).unwrap();
+
+        assert_eq!(first.tiles, second.tiles);
+        assert_eq!(first.roll_tokens, second.roll_tokens);
+        assert_eq!(first.harbors, second.harbors);
+    }
+
+    #[test]
+    fn test_distinct_random_boards() {
+        let mut rng = GameRng::from_seed(25);
+        let boards = distinct_random_boards(
+            &mut rng,
+            BoardShape::Standard,
+            &BoardConstraints::default(),
+            5,
+        );
+        assert_eq!(boards.len(), 5);
+
+        let hashes: HashSet<u64> = boards.iter().map(|board| board.canonical_hash()).collect();
+        assert_eq!(hashes.len(), 5);
+
+        let impossible = BoardConstraints {
+            max_pip_imbalance: Some(0),
+            max_attempts: 50,
+            ..BoardConstraints::default()
+        };
+        assert!(distinct_random_boards(&mut rng, BoardShape::Standard, &impossible, 5).is_empty());
+    }
+
+    #[test]
+    fn test_token_pips() {
+        assert_eq!(RollToken::Two.pips(), 1);
+        assert_eq!(RollToken::Six.pips(), 5);
+        assert_eq!(RollToken::Eight.pips(), 5);
+        assert_eq!(RollToken::Twelve.pips(), 1);
+    }
+}
@@ -0,0 +1,453 @@
+use std::ops::Add;
+use board::{InternalCoord, InternalEdge, TILE_COORD_DIR};
+
+/// The six steps between neighboring hexes in `HexCoord` terms. Direction
+/// `i` leads to the hex beyond the edge between vertices `i` and `i + 1` of
+/// `HexCoord::vertices`.
+pub const HEX_DIRECTIONS: [(i32, i32, i32); 6] = [
+    (1, 0, -1),
+    (0, 1, -1),
+    (-1, 1, 0),
+    (-1, 0, 1),
+    (0, -1, 1),
+    (1, -1, 0),
+];
+
+/// A hex in cube coordinates on the hex grid itself, so neighboring hexes are
+/// one step apart. The board's `InternalCoord` lattice is three times finer,
+/// which leaves room for the vertices between hexes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HexCoord {
+    pub q: i32,
+    pub r: i32,
+    pub s: i32,
+}
+
+/// A vertex of the hex grid, where settlements and cities are built.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct VertexCoord(InternalCoord);
+
+/// The side shared by two neighboring vertices, where roads are built. The
+/// vertices are kept in sorted order, as in `InternalEdge`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct EdgeCoord {
+    a: VertexCoord,
+    b: VertexCoord,
+}
+
+impl HexCoord {
+    pub fn new(q: i32, r: i32, s: i32) -> HexCoord {
+        assert_eq!(q + r + s, 0, "Coordinates do not sum to zero!");
+
+        HexCoord { q, r, s }
+    }
+
+    pub fn origin() -> HexCoord {
+        HexCoord::new(0, 0, 0)
+    }
+
+    /// The hex centered on `coordinate`, or `None` if it is a vertex.
+    pub fn from_internal(coordinate: InternalCoord) -> Option<HexCoord> {
+        let InternalCoord { x, y, z } = coordinate;
+        if (x - y).rem_euclid(3) != 0 {
+            return None;
+        }
+
+        Some(HexCoord::new((x - y) / 3, (y - z) / 3, (z - x) / 3))
+    }
+
+    pub fn to_internal(self) -> InternalCoord {
+        InternalCoord::new(self.q - self.s, self.r - self.q, self.s - self.r)
+    }
+
+    pub fn neighbor(self, direction: usize) -> HexCoord {
+        self + HEX_DIRECTIONS[direction % 6]
+    }
+
+    pub fn neighbors(self) -> Vec<HexCoord> {
+        (0..6).map(|direction| self.neighbor(direction)).collect()
+    }
+
+    /// The number of steps between the two hexes.
+    pub fn distance(self, other: HexCoord) -> u32 {
+        let (dq, dr, ds) = (self.q - other.q, self.r - other.r, self.s - other.s);
+
+        dq.abs().max(dr.abs()).max(ds.abs()) as u32
+    }
+
+    /// The six corners, in the order of `TILE_COORD_DIR`.
+    pub fn vertices(self) -> Vec<VertexCoord> {
+        let center = self.to_internal();
+
+        TILE_COORD_DIR
+            .iter()
+            .map(|&(dx, dy, dz)| {
+                VertexCoord(InternalCoord::new(center.x + dx, center.y + dy, center.z + dz))
+            })
+            .collect()
+    }
+
+    /// The six sides, each between consecutive `vertices`.
+    pub fn edges(self) -> Vec<EdgeCoord> {
+        let vertices = self.vertices();
+
+        (0..6)
+            .map(|index| EdgeCoord::new(vertices[index], vertices[(index + 1) % 6]))
+            .collect()
+    }
+
+    /// Every hex exactly `radius` steps away, going once around the ring.
+    pub fn ring(self, radius: u32) -> Vec<HexCoord> {
+        if radius == 0 {
+            return vec![self];
+        }
+
+        let (dq, dr, ds) = HEX_DIRECTIONS[4];
+        let radius = radius as i32;
+        let mut hex = self + (dq * radius, dr * radius, ds * radius);
+        let mut ring = Vec::new();
+        for direction in 0..6 {
+            for _ in 0..radius {
+                ring.push(hex);
+                hex = hex.neighbor(direction);
+            }
+        }
+
+        ring
+    }
+
+    /// Every hex within `radius` steps, from the center outwards ring by ring.
+    pub fn spiral(self, radius: u32) -> Vec<HexCoord> {
+        (0..(radius + 1)).flat_map(|ring| self.ring(ring)).collect()
+    }
+
+    /// The hexes a straight line from this hex to `other` passes through,
+    /// including both ends. Ties between two hexes go the same way each time.
+    pub fn line_to(self, other: HexCoord) -> Vec<HexCoord> {
+        let steps = self.distance(other);
+        if steps == 0 {
+            return vec![self];
+        }
+
+        // Nudging the line off the edges it would run along avoids ties
+        let lerp = |from: i32, to: i32, nudge: f64, t: f64| {
+            from as f64 + nudge + (to - from) as f64 * t
+        };
+        (0..(steps + 1))
+            .map(|step| {
+                let t = step as f64 / steps as f64;
+                round(
+                    lerp(self.q, other.q, 1e-6, t),
+                    lerp(self.r, other.r, 2e-6, t),
+                    lerp(self.s, other.s, -3e-6, t),
+                )
+            })
+            .collect()
+    }
+
+    /// See `InternalCoord::rotate`.
+    pub fn rotate(self, steps: i32) -> HexCoord {
+        (0..steps.rem_euclid(6)).fold(self, |hex, _| HexCoord::new(-hex.r, -hex.s, -hex.q))
+    }
+
+    /// See `InternalCoord::reflect`.
+    pub fn reflect(self) -> HexCoord {
+        HexCoord::new(-self.s, -self.r, -self.q)
+    }
+}
+
+impl Add<(i32, i32, i32)> for HexCoord {
+    type Output = HexCoord;
+
+    fn add(self, (dq, dr, ds): (i32, i32, i32)) -> HexCoord {
+        HexCoord::new(self.q + dq, self.r + dr, self.s + ds)
+    }
+}
+
+fn round(q: f64, r: f64, s: f64) -> HexCoord {
+    let (mut rounded_q, mut rounded_r, mut rounded_s) = (q.round(), r.round(), s.round());
+    let (error_q, error_r, error_s) =
+        ((rounded_q - q).abs(), (rounded_r - r).abs(), (rounded_s - s).abs());
+
+    // Rounding each part alone can break the zero sum, so the part that moved
+    // the furthest is rebuilt from the other two
+    if error_q > error_r && error_q > error_s {
+        rounded_q = -rounded_r - rounded_s;
+    } else if error_r > error_s {
+        rounded_r = -rounded_q - rounded_s;
+    } else {
+        rounded_s = -rounded_q - rounded_r;
+    }
+
+    HexCoord::new(rounded_q as i32, rounded_r as i32, rounded_s as i32)
+}
+
+impl VertexCoord {
+    /// The vertex at `coordinate`, or `None` if it is the center of a hex.
+    pub fn from_internal(coordinate: InternalCoord) -> Option<VertexCoord> {
+        match HexCoord::from_internal(coordinate) {
+            Some(_) => None,
+            None => Some(VertexCoord(coordinate)),
+        }
+    }
+
+    pub fn to_internal(self) -> InternalCoord {
+        self.0
+    }
+
+    /// The three hexes that meet at this vertex.
+    pub fn hexes(self) -> Vec<HexCoord> {
+        self.0
+            .neighbors()
+            .into_iter()
+            .filter_map(HexCoord::from_internal)
+            .collect()
+    }
+
+    /// The three vertices one road away.
+    pub fn neighbors(self) -> Vec<VertexCoord> {
+        self.0
+            .neighbors()
+            .into_iter()
+            .filter_map(VertexCoord::from_internal)
+            .collect()
+    }
+
+    pub fn edges(self) -> Vec<EdgeCoord> {
+        self.neighbors()
+            .into_iter()
+            .map(|neighbor| EdgeCoord::new(self, neighbor))
+            .collect()
+    }
+
+    pub fn rotate(self, steps: i32) -> VertexCoord {
+        VertexCoord(self.0.rotate(steps))
+    }
+
+    pub fn reflect(self) -> VertexCoord {
+        VertexCoord(self.0.reflect())
+    }
+}
+
+impl EdgeCoord {
+    pub fn new(a: VertexCoord, b: VertexCoord) -> EdgeCoord {
+        assert!(a.0.adjacent(&b.0), "Edge endpoints must be adjacent!");
+
+        if a < b {
+            EdgeCoord { a, b }
+        } else {
+            EdgeCoord { a: b, b: a }
+        }
+    }
+
+    /// The edge along `edge`, or `None` if either end is the center of a hex.
+    pub fn from_internal(edge: InternalEdge) -> Option<EdgeCoord> {
+        let (a, b) = edge.endpoints();
+
+        Some(EdgeCoord::new(VertexCoord::from_internal(a)?, VertexCoord::from_internal(b)?))
+    }
+
+    pub fn to_internal(self) -> InternalEdge {
+        InternalEdge::new(self.a.0, self.b.0)
+    }
+
+    pub fn vertices(self) -> (VertexCoord, VertexCoord) {
+        (self.a, self.b)
+    }
+
+    /// The two hexes on either side of the edge.
+    pub fn hexes(self) -> (HexCoord, HexCoord) {
+        let (first, second) = InternalCoord::adjacent_facing_tiles(&self.a.0, &self.b.0);
+        let hex = |coordinate| {
+            HexCoord::from_internal(coordinate).expect("Both sides of an edge are hexes")
+        };
+
+        (hex(first), hex(second))
+    }
+
+    pub fn rotate(self, steps: i32) -> EdgeCoord {
+        EdgeCoord::new(self.a.rotate(steps), self.b.rotate(steps))
+    }
+
+    pub fn reflect(self) -> EdgeCoord {
+        EdgeCoord::new(self.a.reflect(), self.b.reflect())
+    }
+}
+
+/// Rotations and reflections of the board lattice. Both keep hex centers on
+/// hex centers, so they apply to hexes, vertices and edges alike.
+impl InternalCoord {
+    /// Turns the coordinate `steps` sixths of a turn about the center hex.
+    /// One step takes `TILE_COORD_DIR[i]` to `TILE_COORD_DIR[i + 1]`.
+    pub fn rotate(self, steps: i32) -> InternalCoord {
+        (0..steps.rem_euclid(6)).fold(self, |coordinate, _| {
+            InternalCoord::new(-coordinate.y, -coordinate.z, -coordinate.x)
+        })
+    }
+
+    /// Mirrors the coordinate across the line through the center hex and its
+    /// neighbor at `(2, -1, -1)`.
+    pub fn reflect(self) -> InternalCoord {
+        InternalCoord::new(self.x, self.z, self.y)
+    }
+}
+
+/// Where an index into `TILE_COORD_DIR`, such as a harbor's orientation, ends
+/// up after `InternalCoord::rotate`.
+pub fn rotate_direction(direction: u32, steps: i32) -> u32 {
+    (direction as i32 + steps).rem_euclid(6) as u32
+}
+
+/// Where an index into `TILE_COORD_DIR` ends up after `InternalCoord::reflect`.
+pub fn reflect_direction(direction: u32) -> u32 {
+    (7 - direction % 6) % 6
+}
+
+#[cfg(test)]
+mod geometry_tests {
+    use std::collections::HashSet;
+    use board::{Board, InternalCoord, STANDARD_TILE_LOCATIONS, TILE_COORD_DIR};
+    use geometry::{EdgeCoord, HexCoord, VertexCoord, reflect_direction, rotate_direction};
+
+    fn direction(index: u32) -> InternalCoord {
+        let (x, y, z) = TILE_COORD_DIR[index as usize];
+        InternalCoord::new(x, y, z)
+    }
+
+    #[test]
+    fn test_internal_conversions() {
+        let hex = HexCoord::new(1, -2, 1);
+        assert_eq!(hex.to_internal(), InternalCoord::new(0, -3, 3));
+        assert_eq!(HexCoord::from_internal(hex.to_internal()), Some(hex));
+        assert_eq!(HexCoord::from_internal(InternalCoord::new(1, 0, -1)), None);
+
+        let vertex = VertexCoord::from_internal(InternalCoord::new(1, 0, -1)).unwrap();
+        assert_eq!(vertex.to_internal(), InternalCoord::new(1, 0, -1));
+        assert_eq!(VertexCoord::from_internal(InternalCoord::new(2, -1, -1)), None);
+
+        for neighbor in HexCoord::origin().neighbors() {
+            assert!(HexCoord::origin().to_internal().diagonal_neighbors()
+                .contains(&neighbor.to_internal()));
+        }
+    }
+
+    #[test]
+    fn test_standard_board_shape() {
+        let hexes = HexCoord::origin().spiral(2);
+        let locations: HashSet<InternalCoord> =
+            hexes.iter().map(|hex| hex.to_internal()).collect();
+        assert_eq!(hexes.len(), 19);
+        assert_eq!(locations, STANDARD_TILE_LOCATIONS.iter().cloned().collect());
+
+        let board = Board::balanced_start();
+        let vertices: HashSet<VertexCoord> =
+            hexes.iter().flat_map(|hex| hex.vertices()).collect();
+        let edges: HashSet<EdgeCoord> = hexes.iter().flat_map(|hex| hex.edges()).collect();
+        assert_eq!(vertices.len(), 54);
+        assert_eq!(edges.len(), 72);
+        assert!(edges.iter().all(|edge| board.is_edge(edge.to_internal())));
+    }
+
+    #[test]
+    fn test_rings_and_distance() {
+        let center = HexCoord::new(2, -1, -1);
+
+        assert_eq!(center.ring(0), vec![center]);
+        for radius in 1..5 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|hex| hex.distance(center) == radius));
+            assert_eq!(ring.iter().collect::<HashSet<_>>().len(), ring.len());
+            // Each hex follows on from the one before it
+            assert!((0..ring.len()).all(|i| ring[i].distance(ring[(i + 1) % ring.len()]) == 1));
+        }
+        assert_eq!(center.spiral(3).len(), 37);
+        assert_eq!(HexCoord::origin().distance(HexCoord::new(3, -1, -2)), 3);
+    }
+
+    #[test]
+    fn test_lines() {
+        let start = HexCoord::new(-2, 0, 2);
+        let end = HexCoord::new(3, -1, -2);
+        let line = start.line_to(end);
+
+        assert_eq!(line.len(), 6);
+        assert_eq!(line[0], start);
+        assert_eq!(line[5], end);
+        assert!(line.windows(2).all(|pair| pair[0].distance(pair[1]) == 1));
+        assert_eq!(start.line_to(start), vec![start]);
+        assert_eq!(
+            HexCoord::origin().line_to(HexCoord::new(0, 3, -3)),
+            vec![
+                HexCoord::new(0, 0, 0),
+                HexCoord::new(0, 1, -1),
+                HexCoord::new(0, 2, -2),
+                HexCoord::new(0, 3, -3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vertices_and_edges() {
+        let hex = HexCoord::new(1, 0, -1);
+
+        for vertex in hex.vertices() {
+            assert_eq!(vertex.hexes().len(), 3);
+            assert!(vertex.hexes().contains(&hex));
+            assert_eq!(vertex.neighbors().len(), 3);
+            assert_eq!(vertex.edges().len(), 3);
+        }
+        for edge in hex.edges() {
+            let (first, second) = edge.hexes();
+            assert!(first == hex || second == hex);
+            assert_eq!(first.distance(second), 1);
+
+            let (a, b) = edge.vertices();
+            assert!(hex.vertices().contains(&a) && hex.vertices().contains(&b));
+            assert_eq!(EdgeCoord::from_internal(edge.to_internal()), Some(edge));
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let hex = HexCoord::new(2, -1, -1);
+
+        assert_eq!(hex.rotate(6), hex);
+        assert_eq!(hex.rotate(-1), hex.rotate(5));
+        assert_eq!(hex.rotate(1).to_internal(), hex.to_internal().rotate(1));
+        for index in 0..6 {
+            assert_eq!(direction(index).rotate(1), direction(rotate_direction(index, 1)));
+            assert_eq!(direction(index).rotate(-2), direction(rotate_direction(index, -2)));
+        }
+
+        let tiles: HashSet<InternalCoord> = STANDARD_TILE_LOCATIONS.iter().cloned().collect();
+        for steps in 0..6 {
+            let rotated: HashSet<InternalCoord> =
+                tiles.iter().map(|tile| tile.rotate(steps)).collect();
+            assert_eq!(rotated, tiles);
+        }
+
+        let edge = HexCoord::origin().edges()[0];
+        let (first, second) = edge.rotate(2).hexes();
+        let (rotated_first, rotated_second) = edge.hexes();
+        let rotated: HashSet<HexCoord> =
+            vec![rotated_first.rotate(2), rotated_second.rotate(2)].into_iter().collect();
+        assert_eq!(rotated, vec![first, second].into_iter().collect());
+    }
+
+    #[test]
+    fn test_reflection() {
+        let hex = HexCoord::new(1, -2, 1);
+
+        assert_eq!(hex.reflect().reflect(), hex);
+        assert_eq!(hex.reflect().to_internal(), hex.to_internal().reflect());
+        assert_eq!(HexCoord::new(1, 0, -1).reflect(), HexCoord::new(1, 0, -1));
+        for index in 0..6 {
+            assert_eq!(direction(index).reflect(), direction(reflect_direction(index)));
+        }
+
+        let vertex = HexCoord::origin().vertices()[2];
+        assert_eq!(vertex.reflect().reflect(), vertex);
+        assert_eq!(vertex.reflect().rotate(1), vertex.rotate(-1).reflect());
+    }
+}
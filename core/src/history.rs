@@ -0,0 +1,220 @@
+use error::{ReplayError, ReplayResult};
+use game::{CatanGame, DevelopmentCardType, GameEvent, GameSettings, PlayerAction, PlayerColor,
+           ResourceType};
+
+/// A random result revealed while applying an action.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum RandomOutcome {
+    DiceRolled(u32, u32),
+    ResourceStolen(ResourceType),
+    DevelopmentCardDrawn(DevelopmentCardType),
+}
+
+impl RandomOutcome {
+    /// The random outcomes among `events`, in the order they happened.
+    pub fn from_events(events: &[GameEvent]) -> Vec<RandomOutcome> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                GameEvent::DiceRolled(first, second) => {
+                    Some(RandomOutcome::DiceRolled(first, second))
+                }
+                GameEvent::ResourceStolen(_, _, Some(resource_type)) => {
+                    Some(RandomOutcome::ResourceStolen(resource_type))
+                }
+                GameEvent::DevelopmentCardPurchased(_, Some(card)) => {
+                    Some(RandomOutcome::DevelopmentCardDrawn(card))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// An action that was applied successfully, with whatever it left to chance.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub color: PlayerColor,
+    pub action: PlayerAction,
+    pub outcomes: Vec<RandomOutcome>,
+}
+
+/// How a game was set up and every action applied to it since, in order.
+/// Entries can only be added, never changed or removed.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ActionLog {
+    colors: Vec<PlayerColor>,
+    settings: GameSettings,
+    entries: Vec<LogEntry>,
+}
+
+impl ActionLog {
+    pub fn new(colors: &[PlayerColor], settings: GameSettings) -> ActionLog {
+        ActionLog {
+            colors: colors.to_vec(),
+            settings,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn colors(&self) -> &[PlayerColor] {
+        &self.colors
+    }
+
+    pub fn settings(&self) -> &GameSettings {
+        &self.settings
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn push(&mut self, entry: LogEntry) {
+        self.entries.push(entry);
+    }
+}
+
+impl CatanGame {
+    /// Rebuilds the game as it was after every action in `log`.
+    pub fn replay(log: &ActionLog) -> ReplayResult<CatanGame> {
+        CatanGame::replay_to(log, log.len())
+    }
+
+    /// Rebuilds the game as it was after the first `step` actions in `log`.
+    /// The game's seed decides every random outcome again, so each one is
+    /// checked against the log to catch a log that doesn't match its game.
+    pub fn replay_to(log: &ActionLog, step: usize) -> ReplayResult<CatanGame> {
+        if step > log.len() {
+            return Err(ReplayError::StepOutOfRange(step));
+        }
+
+        let mut game = CatanGame::with_settings(&log.colors, log.settings.clone())
+            .map_err(ReplayError::InvalidSettings)?;
+        for (index, entry) in log.entries[..step].iter().enumerate() {
+            let events = game.apply(entry.color, entry.action.clone())
+                .map_err(|error| ReplayError::ActionRejected(index, error))?;
+            if RandomOutcome::from_events(&events) != entry.outcomes {
+                return Err(ReplayError::OutcomeMismatch(index));
+            }
+        }
+
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use error::{ReplayError, RulesError};
+    use game::{CatanGame, GameSettings, PlayerAction, PlayerColor};
+    use game::test_util::{play_random_action, played_game};
+    use history::{ActionLog, RandomOutcome};
+    use rng::GameRng;
+
+    const COLORS: [PlayerColor; 3] = [PlayerColor::Red, PlayerColor::Blue, PlayerColor::White];
+
+    /// Plays `steps` random legal actions, returning the game and its JSON
+    /// after each step.
+    fn played_states(seed: u64, steps: usize) -> (CatanGame, Vec<String>) {
+        let mut game = played_game(&COLORS, seed, 0);
+        let mut rng = GameRng::from_seed(seed);
+        let mut states = vec![game.to_json().unwrap()];
+
+        for _ in 0..steps {
+            if play_random_action(&mut game, &mut rng).is_none() {
+                break;
+            }
+            states.push(game.to_json().unwrap());
+        }
+
+        (game, states)
+    }
+
+    #[test]
+    fn test_actions_are_logged() {
+        let game = played_game(&COLORS, 20, 200);
+        let log = game.history();
+
+        assert_eq!(log.len(), 200);
+        assert_eq!(log.colors(), &COLORS);
+        assert_eq!(log.settings(), game.settings());
+        assert!(log.entries().iter().any(|entry| {
+            entry.action == PlayerAction::RollDice &&
+                matches!(entry.outcomes[..], [RandomOutcome::DiceRolled(..)])
+        }));
+        assert!(log.entries().iter().all(|entry| {
+            entry.action != PlayerAction::EndTurn || entry.outcomes.is_empty()
+        }));
+    }
+
+    #[test]
+    fn test_rejected_actions_are_not_logged() {
+        let mut game = CatanGame::with_settings(
+            &[PlayerColor::Red, PlayerColor::Blue],
+            GameSettings { seed: 3, ..GameSettings::default() },
+        ).unwrap();
+
+        assert!(game.apply(PlayerColor::Blue, PlayerAction::EndTurn).is_err());
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn test_replay_every_step() {
+        let (game, states) = played_states(21, 150);
+        let log = game.history();
+
+        for (step, state) in states.iter().enumerate() {
+            let replayed = CatanGame::replay_to(log, step).unwrap();
+            assert_eq!(&replayed.to_json().unwrap(), state);
+            assert_eq!(replayed.history().entries(), &log.entries()[..step]);
+        }
+        assert_eq!(CatanGame::replay(log).unwrap().to_json().unwrap(), game.to_json().unwrap());
+        assert_eq!(
+            CatanGame::replay_to(log, log.len() + 1).unwrap_err(),
+            ReplayError::StepOutOfRange(151)
+        );
+    }
+
+    #[test]
+    fn test_logged_game_survives_serialization() {
+        let game = played_game(&COLORS, 22, 120);
+        let json = ::serde_json::to_string(game.history()).unwrap();
+        let log: ActionLog = ::serde_json::from_str(&json).unwrap();
+
+        assert_eq!(&log, game.history());
+        assert_eq!(CatanGame::replay(&log).unwrap().to_json().unwrap(), game.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_tampered_log() {
+        let game = played_game(&COLORS, 23, 150);
+        let log = game.history();
+        let roll = log.entries()
+            .iter()
+            .position(|entry| entry.action == PlayerAction::RollDice)
+            .unwrap();
+
+        let mut value = ::serde_json::to_value(log).unwrap();
+        value["entries"][roll]["outcomes"][0]["DiceRolled"] = ::serde_json::json!([7, 7]);
+        let tampered: ActionLog = ::serde_json::from_value(value).unwrap();
+        assert_eq!(
+            CatanGame::replay(&tampered).unwrap_err(),
+            ReplayError::OutcomeMismatch(roll)
+        );
+
+        let mut value = ::serde_json::to_value(log).unwrap();
+        value["entries"][0]["color"] = ::serde_json::json!("Blue");
+        let tampered: ActionLog = ::serde_json::from_value(value).unwrap();
+        assert_eq!(
+            CatanGame::replay(&tampered).unwrap_err(),
+            ReplayError::ActionRejected(0, RulesError::NotYourTurn(PlayerColor::Blue))
+        );
+    }
+}
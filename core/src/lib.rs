@@ -3,6 +3,7 @@ extern crate rand;
 extern crate log;
 extern crate serde;
 extern crate serde_json;
+extern crate bincode;
 #[macro_use]
 extern crate serde_derive;
 extern crate uuid;
@@ -12,4 +13,18 @@ extern crate byteorder;
 
 pub mod board;
 pub mod game;
-pub mod common;
\ No newline at end of file
+pub mod common;
+pub mod error;
+pub mod phase;
+pub mod production;
+pub mod longest_road;
+pub mod award;
+pub mod trade;
+pub mod rng;
+pub mod generator;
+pub mod map;
+pub mod snapshot;
+pub mod view;
+pub mod history;
+pub mod geometry;
+pub mod symmetry;
@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+use award::award_holder;
+use board::{Board, InternalCoord, InternalEdge};
+use game::PlayerColor;
+
+pub const MINIMUM_LONGEST_ROAD: u32 = 5;
+
+/// Length of the longest continuous road owned by `color`. A road may visit
+/// the same intersection more than once but never reuses a segment, and it
+/// cannot pass through an intersection holding an opponent's building.
+pub fn longest_road(board: &Board, color: PlayerColor) -> u32 {
+    let roads: HashSet<InternalEdge> = board.roads_of(color).into_iter().collect();
+    let starts: HashSet<InternalCoord> = roads
+        .iter()
+        .flat_map(|edge| {
+            let (a, b) = edge.endpoints();
+            vec![a, b].into_iter()
+        })
+        .collect();
+
+    let mut used = HashSet::new();
+    starts
+        .into_iter()
+        .map(|start| longest_from(board, color, &roads, start, &mut used))
+        .max()
+        .unwrap_or(0)
+}
+
+fn longest_from(
+    board: &Board,
+    color: PlayerColor,
+    roads: &HashSet<InternalEdge>,
+    vertex: InternalCoord,
+    used: &mut HashSet<InternalEdge>,
+) -> u32 {
+    let mut longest = 0;
+
+    for edge in board.edges_at(vertex) {
+        if !roads.contains(&edge) || used.contains(&edge) {
+            continue;
+        }
+
+        let next = edge.other_endpoint(vertex).expect(
+            "Edges at a vertex always contain it",
+        );
+        let broken = match board.building_at(next) {
+            Some((owner, _)) => owner != color,
+            None => false,
+        };
+
+        used.insert(edge);
+        let length = if broken {
+            1
+        } else {
+            1 + longest_from(board, color, roads, next, used)
+        };
+        used.remove(&edge);
+
+        longest = longest.max(length);
+    }
+
+    longest
+}
+
+/// Decides who holds Longest Road given every player's current road length.
+pub fn longest_road_holder(
+    lengths: &[(PlayerColor, u32)],
+    holder: Option<PlayerColor>,
+) -> Option<PlayerColor> {
+    award_holder(lengths, holder, MINIMUM_LONGEST_ROAD)
+}
+
+#[cfg(test)]
+mod longest_road_tests {
+    use board::{Board, BuildingType, InternalCoord, InternalEdge};
+    use game::PlayerColor;
+    use longest_road::{longest_road, longest_road_holder};
+
+    const CENTER_RING: [(i32, i32, i32); 7] = [
+        (1, -1, 0),
+        (1, 0, -1),
+        (0, 1, -1),
+        (-1, 1, 0),
+        (-1, 0, 1),
+        (0, -1, 1),
+        (1, -1, 0),
+    ];
+
+    const LINE: [(i32, i32, i32); 6] = [
+        (-1, 0, 1),
+        (-2, 0, 2),
+        (-2, -1, 3),
+        (-1, -2, 3),
+        (-1, -3, 4),
+        (0, -4, 4),
+    ];
+
+    fn coord(vertex: (i32, i32, i32)) -> InternalCoord {
+        InternalCoord::new(vertex.0, vertex.1, vertex.2)
+    }
+
+    fn place_path(board: &mut Board, color: PlayerColor, path: &[(i32, i32, i32)]) {
+        for pair in path.windows(2) {
+            board.place_road(InternalEdge::new(coord(pair[0]), coord(pair[1])), color);
+        }
+    }
+
+    #[test]
+    fn test_no_roads() {
+        let board = Board::balanced_start();
+
+        assert_eq!(longest_road(&board, PlayerColor::Red), 0);
+    }
+
+    #[test]
+    fn test_straight_line() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &LINE[..3]);
+        assert_eq!(longest_road(&board, PlayerColor::Red), 2);
+
+        place_path(&mut board, PlayerColor::Red, &LINE[2..]);
+        assert_eq!(longest_road(&board, PlayerColor::Red), 5);
+        assert_eq!(longest_road(&board, PlayerColor::Blue), 0);
+    }
+
+    #[test]
+    fn test_loop() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &CENTER_RING);
+
+        assert_eq!(longest_road(&board, PlayerColor::Red), 6);
+    }
+
+    #[test]
+    fn test_loop_with_tail() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &CENTER_RING);
+        place_path(&mut board, PlayerColor::Red, &[(1, -1, 0), (2, -2, 0), (3, -2, -1)]);
+
+        // The tail leads into the loop and all the way around it
+        assert_eq!(longest_road(&board, PlayerColor::Red), 8);
+    }
+
+    #[test]
+    fn test_figure_eight() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &CENTER_RING);
+        // The ring around the mountains hex shares the (1, -1, 0) - (1, 0, -1) edge
+        place_path(
+            &mut board,
+            PlayerColor::Red,
+            &[(1, -1, 0), (2, -2, 0), (3, -2, -1), (3, -1, -2), (2, 0, -2), (1, 0, -1)],
+        );
+
+        assert_eq!(longest_road(&board, PlayerColor::Red), 11);
+    }
+
+    #[test]
+    fn test_fork_counts_one_branch() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &[(0, 1, -1), (1, 0, -1), (1, -1, 0)]);
+        place_path(&mut board, PlayerColor::Red, &[(1, -1, 0), (2, -2, 0), (3, -2, -1)]);
+        place_path(&mut board, PlayerColor::Red, &[(1, -1, 0), (0, -1, 1)]);
+
+        assert_eq!(longest_road(&board, PlayerColor::Red), 4);
+    }
+
+    #[test]
+    fn test_opponent_building_breaks_road() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &LINE);
+
+        board.place_building(coord(LINE[2]), PlayerColor::Red, BuildingType::Settlement);
+        assert_eq!(longest_road(&board, PlayerColor::Red), 5);
+
+        board.place_building(coord(LINE[2]), PlayerColor::Blue, BuildingType::Settlement);
+        assert_eq!(longest_road(&board, PlayerColor::Red), 3);
+    }
+
+    #[test]
+    fn test_opponent_building_at_end_does_not_shorten() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &LINE);
+        board.place_building(coord(LINE[0]), PlayerColor::Blue, BuildingType::City);
+
+        assert_eq!(longest_road(&board, PlayerColor::Red), 5);
+    }
+
+    #[test]
+    fn test_disjoint_roads() {
+        let mut board = Board::balanced_start();
+        place_path(&mut board, PlayerColor::Red, &LINE[..4]);
+        place_path(&mut board, PlayerColor::Red, &CENTER_RING[..3]);
+
+        assert_eq!(longest_road(&board, PlayerColor::Red), 3);
+    }
+
+    #[test]
+    fn test_holder_needs_minimum() {
+        let lengths = [(PlayerColor::Red, 4), (PlayerColor::Blue, 2)];
+        assert_eq!(longest_road_holder(&lengths, None), None);
+
+        let lengths = [(PlayerColor::Red, 5), (PlayerColor::Blue, 2)];
+        assert_eq!(longest_road_holder(&lengths, None), Some(PlayerColor::Red));
+    }
+
+    #[test]
+    fn test_holder_keeps_award_on_tie() {
+        let lengths = [(PlayerColor::Red, 6), (PlayerColor::Blue, 6)];
+
+        assert_eq!(
+            longest_road_holder(&lengths, Some(PlayerColor::Red)),
+            Some(PlayerColor::Red)
+        );
+        assert_eq!(longest_road_holder(&lengths, None), None);
+    }
+
+    #[test]
+    fn test_holder_strictly_beaten() {
+        let lengths = [(PlayerColor::Red, 6), (PlayerColor::Blue, 7)];
+
+        assert_eq!(
+            longest_road_holder(&lengths, Some(PlayerColor::Red)),
+            Some(PlayerColor::Blue)
+        );
+    }
+
+    #[test]
+    fn test_broken_holder_with_tied_challengers() {
+        let lengths = [
+            (PlayerColor::Red, 3),
+            (PlayerColor::Blue, 5),
+            (PlayerColor::White, 5),
+        ];
+        assert_eq!(longest_road_holder(&lengths, Some(PlayerColor::Red)), None);
+
+        let lengths = [(PlayerColor::Red, 4), (PlayerColor::Blue, 3)];
+        assert_eq!(longest_road_holder(&lengths, Some(PlayerColor::Red)), None);
+    }
+}
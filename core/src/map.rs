@@ -0,0 +1,316 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use serde_json;
+use board::{Board, HarborType, InternalCoord, InternalTileType, ResourceTileType, RollToken,
+            harbor_building_locations};
+use error::{MapError, MapResult};
+
+/// A board layout as stored in a map file. Positions are hex centers in the
+/// same cube coordinates the board uses, so the center hex is `[0, 0, 0]` and
+/// its neighbors are one step away, such as `[2, -1, -1]`.
+///
+/// Only the layout is stored; roads and buildings are left out.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct MapFile {
+    pub hexes: Vec<MapHex>,
+    #[serde(default)]
+    pub water: Vec<[i32; 3]>,
+    #[serde(default)]
+    pub harbors: Vec<MapHarbor>,
+    #[serde(default)]
+    pub robber: Option<[i32; 3]>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct MapHex {
+    pub position: [i32; 3],
+    pub terrain: ResourceTileType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<u32>,
+}
+
+/// A harbor on a water hex. `orientation` is the direction it faces in
+/// sixths of a turn, as stored in `Board::harbors`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct MapHarbor {
+    pub position: [i32; 3],
+    pub harbor: HarborType,
+    pub orientation: u32,
+}
+
+impl MapFile {
+    pub fn from_board(board: &Board) -> MapFile {
+        let mut hexes: Vec<MapHex> = board
+            .tiles
+            .iter()
+            .filter_map(|(&coordinate, tile)| match *tile {
+                InternalTileType::ResourceTile(terrain) => {
+                    Some(MapHex {
+                        position: position(coordinate),
+                        terrain,
+                        token: board.roll_tokens.get(&coordinate).map(|token| token.value()),
+                    })
+                }
+                InternalTileType::BuildingTile(_) => None,
+            })
+            .collect();
+        hexes.sort_by_key(|hex| hex.position);
+
+        let mut water: Vec<[i32; 3]> = board.water.iter().map(|&water| position(water)).collect();
+        water.sort();
+
+        let mut harbors: Vec<MapHarbor> = board
+            .harbors
+            .iter()
+            .map(|(&coordinate, &(harbor, orientation))| {
+                MapHarbor {
+                    position: position(coordinate),
+                    harbor,
+                    orientation,
+                }
+            })
+            .collect();
+        harbors.sort_by_key(|harbor| harbor.position);
+
+        MapFile {
+            hexes,
+            water,
+            harbors,
+            robber: board.robber.map(position),
+        }
+    }
+
+    pub fn to_board(&self) -> MapResult<Board> {
+        let mut seen = HashSet::new();
+
+        let mut tiles = Vec::new();
+        for hex in self.hexes.iter() {
+            let coordinate = hex_coordinate(hex.position)?;
+            if !seen.insert(coordinate) {
+                return Err(MapError::DuplicateHex(coordinate));
+            }
+
+            let token = match hex.token {
+                None => None,
+                Some(value) if valid_token(hex.terrain, value) => Some(RollToken::new(value)),
+                Some(_) => return Err(MapError::InvalidToken(coordinate)),
+            };
+            tiles.push((coordinate, hex.terrain, token));
+        }
+
+        let mut water = HashSet::new();
+        for &position in self.water.iter() {
+            let coordinate = hex_coordinate(position)?;
+            if !seen.insert(coordinate) {
+                return Err(MapError::DuplicateHex(coordinate));
+            }
+            water.insert(coordinate);
+        }
+
+        let mut harbors = Vec::new();
+        for harbor in self.harbors.iter() {
+            let coordinate = hex_coordinate(harbor.position)?;
+            if !water.contains(&coordinate) || harbor.orientation >= 6 ||
+                harbors.iter().any(|&(other, _, _)| other == coordinate)
+            {
+                return Err(MapError::InvalidHarbor(coordinate));
+            }
+            harbors.push((coordinate, harbor.harbor, harbor.orientation));
+        }
+
+        let mut board = Board::from_tiles(&tiles, &harbors);
+        board.water = water;
+
+        for &(coordinate, _, orientation) in harbors.iter() {
+            let faces_coast = harbor_building_locations(coordinate, orientation)
+                .into_iter()
+                .all(|building| board.building_tile(building).is_some());
+            if !faces_coast {
+                return Err(MapError::InvalidHarbor(coordinate));
+            }
+        }
+
+        // Without a robber in the file it stays on the desert, if there is one
+        if let Some(position) = self.robber {
+            let coordinate = hex_coordinate(position)?;
+            match board.tiles.get(&coordinate) {
+                Some(&InternalTileType::ResourceTile(_)) => board.robber = Some(coordinate),
+                _ => return Err(MapError::InvalidRobber(coordinate)),
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+impl Board {
+    pub fn from_map_file<P: AsRef<Path>>(path: P) -> MapResult<Board> {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+
+        Board::from_map_str(&contents)
+    }
+
+    pub fn to_map_file<P: AsRef<Path>>(&self, path: P) -> MapResult<()> {
+        let contents = self.to_map_string()?;
+        File::create(path)?.write_all(contents.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub fn from_map_str(contents: &str) -> MapResult<Board> {
+        let map: MapFile = serde_json::from_str(contents)?;
+
+        map.to_board()
+    }
+
+    pub fn to_map_string(&self) -> MapResult<String> {
+        Ok(serde_json::to_string_pretty(&MapFile::from_board(self))?)
+    }
+}
+
+fn position(coordinate: InternalCoord) -> [i32; 3] {
+    [coordinate.x, coordinate.y, coordinate.z]
+}
+
+/// Hex centers are the lattice points whose coordinates all leave the same
+/// remainder when divided by three.
+fn hex_coordinate(position: [i32; 3]) -> MapResult<InternalCoord> {
+    let [x, y, z] = position;
+    if x + y + z != 0 || (x - y).rem_euclid(3) != 0 {
+        return Err(MapError::InvalidCoordinate(x, y, z));
+    }
+
+    Ok(InternalCoord::new(x, y, z))
+}
+
+fn valid_token(terrain: ResourceTileType, value: u32) -> bool {
+    terrain != ResourceTileType::Desert && (2..13).contains(&value) && value != 7
+}
+
+#[cfg(test)]
+mod map_tests {
+    use std::env;
+    use std::fs;
+    use board::{Board, BoardShape, HarborType, InternalCoord, ResourceTileType, RollToken};
+    use error::MapError;
+    use map::MapFile;
+
+    const ISLAND: &str = r#"{
+        "hexes": [
+            { "position": [0, 0, 0], "terrain": "Fields", "token": 6 },
+            { "position": [2, -1, -1], "terrain": "Desert" }
+        ],
+        "water": [[1, 1, -2], [-2, 1, 1]],
+        "harbors": [{ "position": [1, 1, -2], "harbor": "Grain", "orientation": 2 }],
+        "robber": [2, -1, -1]
+    }"#;
+
+    fn assert_same_layout(first: &Board, second: &Board) {
+        assert_eq!(first.tiles, second.tiles);
+        assert_eq!(first.roll_tokens, second.roll_tokens);
+        assert_eq!(first.harbors, second.harbors);
+        assert_eq!(first.water, second.water);
+        assert_eq!(first.robber, second.robber);
+    }
+
+    #[test]
+    fn test_load_map() {
+        let board = Board::from_map_str(ISLAND).unwrap();
+
+        assert_eq!(board.resource_tiles().len(), 2);
+        assert_eq!(board.building_tiles().len(), 10);
+        assert_eq!(board.roll_tokens[&InternalCoord::new(0, 0, 0)], RollToken::Six);
+        assert_eq!(board.robber, Some(InternalCoord::new(2, -1, -1)));
+        assert_eq!(
+            board.building_tile(InternalCoord::new(0, 1, -1)).unwrap().harbor_type,
+            Some(HarborType::Grain)
+        );
+        assert_eq!(
+            board.building_tile(InternalCoord::new(1, 0, -1)).unwrap().harbor_type,
+            Some(HarborType::Grain)
+        );
+    }
+
+    #[test]
+    fn test_robber_defaults_to_desert() {
+        let mut map: MapFile = ::serde_json::from_str(ISLAND).unwrap();
+        map.robber = None;
+        assert_eq!(map.to_board().unwrap().robber, Some(InternalCoord::new(2, -1, -1)));
+
+        map.hexes[1].terrain = ResourceTileType::Hills;
+        assert_eq!(map.to_board().unwrap().robber, None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for &shape in [BoardShape::Standard, BoardShape::Extended].iter() {
+            let board = Board::balanced(shape);
+            let loaded = Board::from_map_str(&board.to_map_string().unwrap()).unwrap();
+
+            assert_same_layout(&board, &loaded);
+        }
+    }
+
+    #[test]
+    fn test_map_file() {
+        let path = env::temp_dir().join(format!("catan_map_test_{}.json", ::std::process::id()));
+        let board = Board::balanced_start();
+
+        board.to_map_file(&path).unwrap();
+        let loaded = Board::from_map_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_same_layout(&board, &loaded.unwrap());
+        assert!(matches!(Board::from_map_file(&path), Err(MapError::Io(_))));
+    }
+
+    #[test]
+    fn test_invalid_maps() {
+        let map: MapFile = ::serde_json::from_str(ISLAND).unwrap();
+
+        let mut off_lattice = map.clone();
+        off_lattice.hexes[1].position = [1, -1, 0];
+        assert!(matches!(off_lattice.to_board(), Err(MapError::InvalidCoordinate(1, -1, 0))));
+
+        let mut duplicate = map.clone();
+        duplicate.water.push([0, 0, 0]);
+        assert!(matches!(duplicate.to_board(), Err(MapError::DuplicateHex(_))));
+
+        let mut desert_token = map.clone();
+        desert_token.hexes[1].token = Some(8);
+        assert!(matches!(desert_token.to_board(), Err(MapError::InvalidToken(_))));
+
+        let mut seven = map.clone();
+        seven.hexes[0].token = Some(7);
+        assert!(matches!(seven.to_board(), Err(MapError::InvalidToken(_))));
+
+        let mut facing_sea = map.clone();
+        facing_sea.harbors[0].orientation = 1;
+        assert!(matches!(facing_sea.to_board(), Err(MapError::InvalidHarbor(_))));
+
+        let mut on_land = map.clone();
+        on_land.harbors[0].position = [0, 0, 0];
+        assert!(matches!(on_land.to_board(), Err(MapError::InvalidHarbor(_))));
+
+        let mut robber_at_sea = map.clone();
+        robber_at_sea.robber = Some([-2, 1, 1]);
+        assert!(matches!(robber_at_sea.to_board(), Err(MapError::InvalidRobber(_))));
+
+        assert!(matches!(Board::from_map_str("{ \"hexes\": 3 }"), Err(MapError::Serde(_))));
+    }
+
+    #[test]
+    fn test_terrain_names() {
+        let map = MapFile::from_board(&Board::balanced_start());
+
+        assert_eq!(map.hexes.len(), 19);
+        assert_eq!(map.water.len(), 18);
+        assert!(map.hexes.iter().any(|hex| {
+            hex.position == [0, 0, 0] && hex.terrain == ResourceTileType::Desert &&
+                hex.token.is_none()
+        }));
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use common::pairs;
+use board::InternalCoord;
+use game::PlayerColor;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum SetupRound {
+    First,
+    Second,
+}
+
+/// Each setup turn places a settlement and then a road touching it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum SetupStep {
+    Settlement,
+    Road(InternalCoord),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub enum GamePhase {
+    Setup(SetupRound, SetupStep),
+    PreRoll,
+    Main,
+    Discard(#[serde(with = "pairs")] HashMap<PlayerColor, u32>),
+    MoveRobber,
+    /// Between turns in a 5-6 player game the other players get a chance to
+    /// build, one at a time. Holds the player whose turn just ended.
+    SpecialBuilding(PlayerColor),
+    GameOver(PlayerColor),
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum ActionKind {
+    RollDice,
+    BuildRoad,
+    BuildSettlement,
+    BuildCity,
+    PurchaseDevelopmentCard,
+    PlayDevelopmentCard,
+    TradeResources,
+    RespondToTrade,
+    Discard,
+    MoveRobber,
+    EndTurn,
+}
+
+impl GamePhase {
+    /// The kinds of action the phase gives the current player. Discards and
+    /// answers to trade offers, which can come from any player, are added by
+    /// `CatanGame::allowed_actions`.
+    pub fn current_player_actions(&self) -> Vec<ActionKind> {
+        match *self {
+            GamePhase::Setup(_, SetupStep::Settlement) => vec![ActionKind::BuildSettlement],
+            GamePhase::Setup(_, SetupStep::Road(_)) => vec![ActionKind::BuildRoad],
+            // A knight may be played before rolling
+            GamePhase::PreRoll => vec![ActionKind::RollDice, ActionKind::PlayDevelopmentCard],
+            GamePhase::Main => {
+                vec![
+                    ActionKind::BuildRoad,
+                    ActionKind::BuildSettlement,
+                    ActionKind::BuildCity,
+                    ActionKind::PurchaseDevelopmentCard,
+                    ActionKind::PlayDevelopmentCard,
+                    ActionKind::TradeResources,
+                    ActionKind::EndTurn,
+                ]
+            }
+            GamePhase::Discard(_) => Vec::new(),
+            GamePhase::MoveRobber => vec![ActionKind::MoveRobber],
+            GamePhase::SpecialBuilding(_) => {
+                vec![
+                    ActionKind::BuildRoad,
+                    ActionKind::BuildSettlement,
+                    ActionKind::BuildCity,
+                    ActionKind::PurchaseDevelopmentCard,
+                    ActionKind::EndTurn,
+                ]
+            }
+            GamePhase::GameOver(_) => Vec::new(),
+        }
+    }
+
+    pub fn is_setup(&self) -> bool {
+        matches!(*self, GamePhase::Setup(..))
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use board::{Board, BuildingType, InternalTileType};
+use common::GameResource;
+use game::{PlayerColor, ResourceCollection, ResourceType};
+
+/// Resources every player's buildings earn from `roll`, ignoring what the bank
+/// can actually pay.
+pub fn owed_resources(board: &Board, roll: u32) -> HashMap<PlayerColor, ResourceCollection> {
+    let mut owed: HashMap<PlayerColor, ResourceCollection> = HashMap::new();
+
+    for (&coordinate, token) in board.roll_tokens.iter() {
+        if *token != roll || board.is_blocked(coordinate) {
+            continue;
+        }
+
+        let resource_type = match board.tiles.get(&coordinate) {
+            Some(&InternalTileType::ResourceTile(tile_type)) => tile_type.into_resource_type(),
+            _ => None,
+        };
+        let resource_type = match resource_type {
+            Some(resource_type) => resource_type,
+            None => continue,
+        };
+
+        for vertex in board.adjacent_building_tiles(coordinate) {
+            if let Some((owner, building)) = board.building_at(vertex) {
+                owed.entry(owner).or_default()[resource_type] += match building {
+                    BuildingType::City => 2,
+                    _ => 1,
+                };
+            }
+        }
+    }
+
+    owed
+}
+
+/// Limits `owed` to what `bank` holds. When the bank cannot pay everyone in
+/// full for a resource, a lone recipient takes whatever is left, and if
+/// several players are owed that resource none of them receive any.
+pub fn apply_shortage_rule(
+    mut owed: HashMap<PlayerColor, ResourceCollection>,
+    bank: &ResourceCollection,
+) -> HashMap<PlayerColor, ResourceCollection> {
+    for resource_type in ResourceType::all_variants() {
+        let demand: u32 = owed.values().map(|resources| resources[resource_type]).sum();
+        if demand <= bank[resource_type] {
+            continue;
+        }
+
+        let recipients = owed.values()
+            .filter(|resources| resources[resource_type] > 0)
+            .count();
+        for resources in owed.values_mut() {
+            if resources[resource_type] > 0 {
+                resources[resource_type] = if recipients == 1 {
+                    bank[resource_type]
+                } else {
+                    0
+                };
+            }
+        }
+    }
+
+    owed.retain(|_, resources| resources.magnitude() > 0);
+
+    owed
+}
+
+#[cfg(test)]
+mod production_tests {
+    use board::{Board, BuildingType, InternalCoord};
+    use game::{PlayerColor, ResourceCollection, ResourceType};
+    use production::{apply_shortage_rule, owed_resources};
+    use std::collections::HashMap;
+
+    fn board_with_buildings() -> Board {
+        let mut board = Board::balanced_start();
+        // Touches the mountains (12), forest (2) and desert hexes
+        board.place_building(
+            InternalCoord::new(1, -1, 0),
+            PlayerColor::Red,
+            BuildingType::City,
+        );
+        // Touches the hills (6), mountains (12) and forest (4) hexes
+        board.place_building(
+            InternalCoord::new(3, -2, -1),
+            PlayerColor::Blue,
+            BuildingType::Settlement,
+        );
+
+        board
+    }
+
+    #[test]
+    fn test_owed_resources() {
+        let board = board_with_buildings();
+
+        let owed = owed_resources(&board, 12);
+        assert_eq!(owed.len(), 2);
+        assert_eq!(owed[&PlayerColor::Red], ResourceCollection::new(2, 0, 0, 0, 0));
+        assert_eq!(owed[&PlayerColor::Blue], ResourceCollection::new(1, 0, 0, 0, 0));
+
+        let owed = owed_resources(&board, 6);
+        assert_eq!(owed.len(), 1);
+        assert_eq!(owed[&PlayerColor::Blue], ResourceCollection::new(0, 1, 0, 0, 0));
+
+        assert!(owed_resources(&board, 7).is_empty());
+        assert!(owed_resources(&board, 3).is_empty());
+    }
+
+    #[test]
+    fn test_robber_blocks_production() {
+        let mut board = board_with_buildings();
+        board.robber = Some(InternalCoord::new(2, -1, -1));
+
+        assert!(owed_resources(&board, 12).is_empty());
+        assert_eq!(owed_resources(&board, 6).len(), 1);
+    }
+
+    #[test]
+    fn test_full_bank_pays_everyone() {
+        let owed = owed_resources(&board_with_buildings(), 12);
+        let bank = ResourceCollection::new(3, 0, 0, 0, 0);
+
+        assert_eq!(apply_shortage_rule(owed.clone(), &bank), owed);
+    }
+
+    #[test]
+    fn test_shortage_with_several_recipients() {
+        let mut owed = owed_resources(&board_with_buildings(), 12);
+        owed.get_mut(&PlayerColor::Blue).unwrap()[ResourceType::Wool] = 1;
+        let bank = ResourceCollection::new(2, 0, 0, 5, 0);
+
+        let paid = apply_shortage_rule(owed, &bank);
+        assert_eq!(paid.len(), 1);
+        assert_eq!(paid[&PlayerColor::Blue], ResourceCollection::new(0, 0, 0, 1, 0));
+    }
+
+    #[test]
+    fn test_shortage_with_single_recipient() {
+        let mut owed = HashMap::new();
+        owed.insert(PlayerColor::Red, ResourceCollection::new(2, 0, 1, 0, 0));
+        let bank = ResourceCollection::new(1, 0, 0, 0, 0);
+
+        let paid = apply_shortage_rule(owed, &bank);
+        assert_eq!(paid[&PlayerColor::Red], ResourceCollection::new(1, 0, 0, 0, 0));
+    }
+}
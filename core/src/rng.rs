@@ -0,0 +1,84 @@
+use rand::Rng;
+
+/// A small xorshift generator whose whole state can be stored and restored,
+/// so a game started from a seed plays out the same way every time.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct GameRng {
+    state: [u32; 4],
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> GameRng {
+        // Spread the seed over the state with splitmix64 so that similar
+        // seeds still start far apart.
+        let mut mix = seed;
+        let mut next = || {
+            mix = mix.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = mix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        let (first, second) = (next(), next());
+        let mut state = [
+            first as u32,
+            (first >> 32) as u32,
+            second as u32,
+            (second >> 32) as u32,
+        ];
+
+        // Xorshift never leaves the all-zero state
+        if state == [0; 4] {
+            state[0] = 1;
+        }
+
+        GameRng { state }
+    }
+}
+
+impl Rng for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        let t = self.state[0] ^ (self.state[0] << 11);
+        self.state[0] = self.state[1];
+        self.state[1] = self.state[2];
+        self.state[2] = self.state[3];
+        self.state[3] ^= (self.state[3] >> 19) ^ t ^ (t >> 8);
+
+        self.state[3]
+    }
+}
+
+#[cfg(test)]
+mod rng_tests {
+    use rand::Rng;
+    use rng::GameRng;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut first = GameRng::from_seed(42);
+        let mut second = GameRng::from_seed(42);
+
+        for _ in 0..100 {
+            assert_eq!(first.next_u32(), second.next_u32());
+        }
+    }
+
+    #[test]
+    fn test_different_seeds_differ() {
+        let mut first = GameRng::from_seed(1);
+        let mut second = GameRng::from_seed(2);
+
+        let first: Vec<u32> = (0..8).map(|_| first.next_u32()).collect();
+        let second: Vec<u32> = (0..8).map(|_| second.next_u32()).collect();
+        assert!(first != second);
+    }
+
+    #[test]
+    fn test_clone_continues_sequence() {
+        let mut rng = GameRng::from_seed(7);
+        rng.next_u32();
+        let mut copy = rng.clone();
+
+        assert_eq!(rng.gen_range(0, 1000), copy.gen_range(0, 1000));
+    }
+}
@@ -0,0 +1,105 @@
+use bincode;
+use serde_json;
+use game::CatanGame;
+
+/// Saving and loading whole games. JSON is readable and suits test fixtures,
+/// while the binary form is compact enough for server snapshots. Both keep
+/// everything, including the random generator, so a loaded game carries on
+/// exactly as the original would have.
+impl CatanGame {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<CatanGame> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<CatanGame> {
+        bincode::deserialize(bytes)
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use std::collections::HashMap;
+    use board::{Board, BoardShape};
+    use game::{CatanGame, PlayerColor};
+    use game::test_util::{play_random_action, played_game};
+    use phase::GamePhase;
+    use rng::GameRng;
+    use serde_json;
+
+    const COLORS: [PlayerColor; 3] = [PlayerColor::Red, PlayerColor::Blue, PlayerColor::White];
+
+    #[test]
+    fn test_json_round_trip() {
+        let game = played_game(&COLORS, 7, 150);
+        let json = game.to_json().unwrap();
+        let loaded = CatanGame::from_json(&json).unwrap();
+
+        assert_eq!(loaded.to_json().unwrap(), json);
+        assert_eq!(loaded.phase(), game.phase());
+        assert_eq!(loaded.board().roads, game.board().roads);
+        assert_eq!(loaded.development_deck_size(), game.development_deck_size());
+        for player in game.players() {
+            let other = loaded.player(player.color()).unwrap();
+            assert_eq!(other.resources(), player.resources());
+            assert_eq!(other.development_cards(), player.development_cards());
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let game = played_game(&COLORS, 8, 150);
+        let bytes = game.to_bytes().unwrap();
+        let loaded = CatanGame::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.to_bytes().unwrap(), bytes);
+        assert_eq!(loaded.to_json().unwrap(), game.to_json().unwrap());
+        assert!(bytes.len() < game.to_json().unwrap().len());
+    }
+
+    #[test]
+    fn test_loaded_game_continues_identically() {
+        let mut game = played_game(&COLORS, 9, 100);
+        let mut loaded = CatanGame::from_bytes(&game.to_bytes().unwrap()).unwrap();
+
+        let mut first_rng = GameRng::from_seed(10);
+        let mut second_rng = GameRng::from_seed(10);
+        for _ in 0..100 {
+            play_random_action(&mut game, &mut first_rng);
+            play_random_action(&mut loaded, &mut second_rng);
+        }
+
+        assert_eq!(loaded.to_json().unwrap(), game.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_discard_phase_round_trip() {
+        let mut pending = HashMap::new();
+        pending.insert(PlayerColor::Blue, 4);
+        pending.insert(PlayerColor::White, 5);
+        let phase = GamePhase::Discard(pending);
+
+        let json = serde_json::to_string(&phase).unwrap();
+        assert_eq!(serde_json::from_str::<GamePhase>(&json).unwrap(), phase);
+    }
+
+    #[test]
+    fn test_board_round_trip() {
+        let board = Board::balanced(BoardShape::Extended);
+        let json = serde_json::to_string(&board).unwrap();
+        let loaded: Board = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.tiles, board.tiles);
+        assert_eq!(loaded.roll_tokens, board.roll_tokens);
+        assert_eq!(loaded.harbors, board.harbors);
+        assert_eq!(loaded.water, board.water);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+    }
+}
@@ -0,0 +1,231 @@
+use bincode;
+use board::{Board, InternalCoord, InternalEdge};
+use geometry::{reflect_direction, rotate_direction};
+
+/// One of the 12 ways to turn or flip the board about its center hex: an
+/// optional reflection followed by a rotation.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct Symmetry {
+    /// Sixths of a turn, as in `InternalCoord::rotate`.
+    pub rotation: u32,
+    /// Whether to apply `InternalCoord::reflect` before rotating.
+    pub reflected: bool,
+}
+
+impl Symmetry {
+    pub fn identity() -> Symmetry {
+        Symmetry {
+            rotation: 0,
+            reflected: false,
+        }
+    }
+
+    pub fn all() -> Vec<Symmetry> {
+        [false, true]
+            .iter()
+            .flat_map(|&reflected| {
+                (0..6).map(move |rotation| Symmetry { rotation, reflected })
+            })
+            .collect()
+    }
+
+    pub fn apply(self, coordinate: InternalCoord) -> InternalCoord {
+        let coordinate = if self.reflected {
+            coordinate.reflect()
+        } else {
+            coordinate
+        };
+
+        coordinate.rotate(self.rotation as i32)
+    }
+
+    pub fn apply_edge(self, edge: InternalEdge) -> InternalEdge {
+        let (a, b) = edge.endpoints();
+
+        InternalEdge::new(self.apply(a), self.apply(b))
+    }
+
+    /// Where an index into `TILE_COORD_DIR` ends up.
+    pub fn apply_direction(self, direction: u32) -> u32 {
+        let direction = if self.reflected {
+            reflect_direction(direction)
+        } else {
+            direction
+        };
+
+        rotate_direction(direction, self.rotation as i32)
+    }
+
+    /// Where a harbor facing `axis` ends up facing. The axis isn't a direction
+    /// itself: the harbor serves the vertices in directions `axis + 2` and
+    /// `axis + 3`, and a reflection swaps which of those comes first.
+    pub fn apply_harbor_axis(self, axis: u32) -> u32 {
+        let first = if self.reflected {
+            self.apply_direction((axis + 3) % 6)
+        } else {
+            self.apply_direction((axis + 2) % 6)
+        };
+
+        rotate_direction(first, -2)
+    }
+}
+
+impl Board {
+    /// The board turned or flipped by `symmetry`, with everything on it.
+    pub fn transformed(&self, symmetry: Symmetry) -> Board {
+        Board {
+            tiles: self.tiles
+                .iter()
+                .map(|(&coordinate, &tile)| (symmetry.apply(coordinate), tile))
+                .collect(),
+            roll_tokens: self.roll_tokens
+                .iter()
+                .map(|(&coordinate, &token)| (symmetry.apply(coordinate), token))
+                .collect(),
+            harbors: self.harbors
+                .iter()
+                .map(|(&coordinate, &(harbor, axis))| {
+                    (symmetry.apply(coordinate), (harbor, symmetry.apply_harbor_axis(axis)))
+                })
+                .collect(),
+            roads: self.roads
+                .iter()
+                .map(|(&edge, &color)| (symmetry.apply_edge(edge), color))
+                .collect(),
+            robber: self.robber.map(|robber| symmetry.apply(robber)),
+            water: self.water.iter().map(|&water| symmetry.apply(water)).collect(),
+        }
+    }
+
+    /// The same board for every rotation and reflection of it: whichever of
+    /// the 12 has the smallest encoding.
+    pub fn canonical(&self) -> Board {
+        Symmetry::all()
+            .into_iter()
+            .map(|symmetry| self.transformed(symmetry))
+            .min_by_key(encode)
+            .expect("There is always at least one symmetry")
+    }
+
+    /// A hash of `canonical` that stays the same between runs and builds, so
+    /// it can be stored, for example as the key of an opening book. Boards
+    /// that are rotations or reflections of each other hash the same; see
+    /// `generator::distinct_random_boards` for skipping them.
+    pub fn canonical_hash(&self) -> u64 {
+        fnv1a(&encode(&self.canonical()))
+    }
+}
+
+/// Boards serialize their maps in sorted order, so equal boards always
+/// encode to the same bytes.
+fn encode(board: &Board) -> Vec<u8> {
+    bincode::serialize(board).expect("Boards can always be serialized")
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The 64-bit FNV-1a hash. Unlike the standard library's hasher it has no
+/// random keys, so values are the same every time.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod symmetry_tests {
+    use std::collections::HashSet;
+    use board::{Board, BoardShape, InternalCoord, InternalEdge, harbor_building_locations};
+    use game::PlayerColor;
+    use generator::{BoardConstraints, random_board};
+    use rng::GameRng;
+    use symmetry::{Symmetry, encode, fnv1a};
+
+    fn played_board() -> Board {
+        let mut board = Board::balanced_start();
+        board.place_road(
+            InternalEdge::new(InternalCoord::new(1, -1, 0), InternalCoord::new(1, 0, -1)),
+            PlayerColor::Red,
+        );
+        board.robber = Some(InternalCoord::new(2, -1, -1));
+
+        board
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let symmetries = Symmetry::all();
+        assert_eq!(symmetries.len(), 12);
+        assert!(symmetries.contains(&Symmetry::identity()));
+
+        let board = played_board();
+        assert_eq!(encode(&board.transformed(Symmetry::identity())), encode(&board));
+
+        // The balanced board has no symmetry of its own, so every image differs
+        let images: HashSet<Vec<u8>> = symmetries
+            .iter()
+            .map(|&symmetry| encode(&board.transformed(symmetry)))
+            .collect();
+        assert_eq!(images.len(), 12);
+    }
+
+    #[test]
+    fn test_transformed_board_is_consistent() {
+        let board = played_board();
+
+        for symmetry in Symmetry::all() {
+            let transformed = board.transformed(symmetry);
+
+            assert_eq!(transformed.edges().len(), 72);
+            assert_eq!(transformed.roads.len(), 1);
+            assert!(transformed.roads.keys().all(|&edge| transformed.is_edge(edge)));
+            assert!(transformed.resource_tiles().contains(&transformed.robber.unwrap()));
+            for (&coordinate, &(harbor, axis)) in transformed.harbors.iter() {
+                for building in harbor_building_locations(coordinate, axis) {
+                    let tile = transformed.building_tile(building).unwrap();
+                    assert_eq!(tile.harbor_type, Some(harbor));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let board = played_board();
+        let canonical = encode(&board.canonical());
+        let hash = board.canonical_hash();
+
+        for symmetry in Symmetry::all() {
+            let image = board.transformed(symmetry);
+            assert_eq!(encode(&image.canonical()), canonical);
+            assert_eq!(image.canonical_hash(), hash);
+        }
+        assert_eq!(encode(&board.canonical().canonical()), canonical);
+        assert!(Board::balanced_start().canonical_hash() != hash);
+    }
+
+    #[test]
+    fn test_dedupe_generated_boards() {
+        let mut rng = GameRng::from_seed(25);
+        let constraints = BoardConstraints::default();
+        let boards: Vec<Board> = (0..5)
+            .map(|_| random_board(&mut rng, BoardShape::Standard, &constraints).unwrap())
+            .collect();
+
+        let mut seen = HashSet::new();
+        for (index, board) in boards.iter().enumerate() {
+            let symmetry = Symmetry::all()[index * 2 % 12];
+            assert!(seen.insert(board.canonical_hash()));
+            assert!(!seen.insert(board.transformed(symmetry).canonical_hash()));
+        }
+        assert_eq!(seen.len(), 5);
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use board::Board;
+use common::pairs;
+use game::{ALL_RESOURCE_TYPES, PlayerColor, ResourceCollection, ResourceType};
+
+pub const DEFAULT_MARITIME_RATE: u32 = 4;
+pub const GENERIC_HARBOR_RATE: u32 = 3;
+pub const SPECIAL_HARBOR_RATE: u32 = 2;
+
+/// The best number of cards `color` must give the bank for one card of their
+/// choice, for each resource they could give. Settlements and cities on a
+/// harbor improve the rate.
+pub fn maritime_rates(board: &Board, color: PlayerColor) -> HashMap<ResourceType, u32> {
+    let mut rates: HashMap<ResourceType, u32> = ALL_RESOURCE_TYPES
+        .iter()
+        .map(|&resource_type| (resource_type, DEFAULT_MARITIME_RATE))
+        .collect();
+
+    for coordinate in board.building_tiles() {
+        match board.building_at(coordinate) {
+            Some((owner, _)) if owner == color => {}
+            _ => continue,
+        }
+        let harbor_type = match board.building_tile(coordinate).and_then(|tile| tile.harbor_type) {
+            Some(harbor_type) => harbor_type,
+            None => continue,
+        };
+
+        for (resource_type, rate) in rates.iter_mut() {
+            let harbor_rate = match harbor_type.into_resource_type() {
+                None => GENERIC_HARBOR_RATE,
+                Some(harbor_resource) if harbor_resource == *resource_type => {
+                    SPECIAL_HARBOR_RATE
+                }
+                Some(_) => continue,
+            };
+            *rate = (*rate).min(harbor_rate);
+        }
+    }
+
+    rates
+}
+
+/// How many cards of their choice `offer` is worth at `rates`. Cards beyond a
+/// whole multiple of a resource's rate are not worth anything.
+pub fn maritime_value(rates: &HashMap<ResourceType, u32>, offer: &ResourceCollection) -> u32 {
+    ALL_RESOURCE_TYPES
+        .iter()
+        .map(|resource_type| offer[*resource_type] / rates[resource_type])
+        .sum()
+}
+
+/// Whether giving exactly `offer` for `receipt` is a fair trade at `rates`:
+/// every resource given is a whole multiple of its rate, the receipt matches
+/// the value, and no resource is both given and received.
+pub fn is_valid_maritime_trade(
+    rates: &HashMap<ResourceType, u32>,
+    offer: &ResourceCollection,
+    receipt: &ResourceCollection,
+) -> bool {
+    let whole_multiples = ALL_RESOURCE_TYPES
+        .iter()
+        .all(|resource_type| offer[*resource_type].is_multiple_of(rates[resource_type]));
+    let disjoint = ALL_RESOURCE_TYPES
+        .iter()
+        .all(|resource_type| offer[*resource_type] == 0 || receipt[*resource_type] == 0);
+
+    whole_multiples && disjoint && receipt.magnitude() > 0 &&
+        maritime_value(rates, offer) == receipt.magnitude()
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TradeOfferId(pub u32);
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum TradeResponse {
+    Accept,
+    Reject,
+}
+
+/// A proposal from one player to trade `offer` for `request`, made either to
+/// a single player or to everyone at the table.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TradeOffer {
+    id: TradeOfferId,
+    from: PlayerColor,
+    to: Option<PlayerColor>,
+    offer: ResourceCollection,
+    request: ResourceCollection,
+    counter_to: Option<TradeOfferId>,
+    #[serde(with = "pairs")]
+    responses: HashMap<PlayerColor, TradeResponse>,
+}
+
+impl TradeOffer {
+    pub fn id(&self) -> TradeOfferId {
+        self.id
+    }
+
+    pub fn from(&self) -> PlayerColor {
+        self.from
+    }
+
+    /// The player the offer is made to, or `None` if anyone may take it.
+    pub fn to(&self) -> Option<PlayerColor> {
+        self.to
+    }
+
+    /// What the proposing player gives.
+    pub fn offer(&self) -> &ResourceCollection {
+        &self.offer
+    }
+
+    /// What the proposing player wants in return.
+    pub fn request(&self) -> &ResourceCollection {
+        &self.request
+    }
+
+    /// The offer this one was made in response to, if it is a counter-offer.
+    pub fn counter_to(&self) -> Option<TradeOfferId> {
+        self.counter_to
+    }
+
+    pub fn response(&self, color: PlayerColor) -> Option<TradeResponse> {
+        self.responses.get(&color).cloned()
+    }
+
+    pub fn is_open_to(&self, color: PlayerColor) -> bool {
+        color != self.from && self.to.is_none_or(|to| to == color)
+    }
+}
+
+/// The trade offers open during the current turn.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradeBook {
+    next_id: u32,
+    offers: Vec<TradeOffer>,
+}
+
+impl TradeBook {
+    pub fn offers(&self) -> &[TradeOffer] {
+        &self.offers
+    }
+
+    pub fn get(&self, id: TradeOfferId) -> Option<&TradeOffer> {
+        self.offers.iter().find(|offer| offer.id == id)
+    }
+
+    pub fn post(
+        &mut self,
+        from: PlayerColor,
+        to: Option<PlayerColor>,
+        offer: ResourceCollection,
+        request: ResourceCollection,
+        counter_to: Option<TradeOfferId>,
+    ) -> &TradeOffer {
+        let id = TradeOfferId(self.next_id);
+        self.next_id += 1;
+
+        self.offers.push(TradeOffer {
+            id,
+            from,
+            to,
+            offer,
+            request,
+            counter_to,
+            responses: HashMap::new(),
+        });
+
+        self.offers.last().expect("An offer was just posted")
+    }
+
+    pub fn respond(&mut self, id: TradeOfferId, color: PlayerColor, response: TradeResponse) {
+        if let Some(offer) = self.offers.iter_mut().find(|offer| offer.id == id) {
+            offer.responses.insert(color, response);
+        }
+    }
+
+    pub fn remove(&mut self, id: TradeOfferId) -> Option<TradeOffer> {
+        let index = self.offers.iter().position(|offer| offer.id == id)?;
+
+        Some(self.offers.remove(index))
+    }
+
+    /// Withdraws every open offer, returning their IDs. Offer IDs are never
+    /// reused, so a stale ID can't refer to a later offer.
+    pub fn clear(&mut self) -> Vec<TradeOfferId> {
+        self.offers.drain(..).map(|offer| offer.id).collect()
+    }
+}
+
+#[cfg(test)]
+mod trade_tests {
+    use board::{Board, BuildingType, InternalCoord};
+    use game::{PlayerColor, ResourceCollection, ResourceType};
+    use trade::{TradeBook, TradeOfferId, TradeResponse, is_valid_maritime_trade, maritime_rates,
+                maritime_value};
+
+    #[test]
+    fn test_default_rates() {
+        let board = Board::balanced_start();
+        let rates = maritime_rates(&board, PlayerColor::Red);
+
+        assert_eq!(rates.len(), 5);
+        assert!(rates.values().all(|&rate| rate == 4));
+    }
+
+    #[test]
+    fn test_harbor_rates() {
+        let mut board = Board::balanced_start();
+        // Generic harbor
+        board.place_building(
+            InternalCoord::new(5, -3, -2),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        let rates = maritime_rates(&board, PlayerColor::Red);
+        assert!(rates.values().all(|&rate| rate == 3));
+        assert_eq!(maritime_rates(&board, PlayerColor::Blue)[&ResourceType::Ore], 4);
+
+        // Brick harbor
+        board.place_building(
+            InternalCoord::new(3, 1, -4),
+            PlayerColor::Red,
+            BuildingType::City,
+        );
+        let rates = maritime_rates(&board, PlayerColor::Red);
+        assert_eq!(rates[&ResourceType::Brick], 2);
+        assert_eq!(rates[&ResourceType::Ore], 3);
+    }
+
+    #[test]
+    fn test_maritime_value() {
+        let mut board = Board::balanced_start();
+        board.place_building(
+            InternalCoord::new(4, 0, -4),
+            PlayerColor::Red,
+            BuildingType::Settlement,
+        );
+        let rates = maritime_rates(&board, PlayerColor::Red);
+
+        assert_eq!(maritime_value(&rates, &ResourceCollection::new(5, 5, 0, 0, 0)), 3);
+        assert_eq!(maritime_value(&rates, &ResourceCollection::new(3, 1, 0, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_valid_maritime_trade() {
+        let rates = maritime_rates(&Board::balanced_start(), PlayerColor::Red);
+        let offer = ResourceCollection::new(4, 0, 0, 0, 4);
+
+        assert!(is_valid_maritime_trade(&rates, &offer, &ResourceCollection::new(0, 1, 1, 0, 0)));
+        assert!(!is_valid_maritime_trade(&rates, &offer, &ResourceCollection::new(0, 1, 0, 0, 0)));
+        assert!(!is_valid_maritime_trade(&rates, &offer, &ResourceCollection::new(1, 1, 0, 0, 0)));
+        assert!(!is_valid_maritime_trade(
+            &rates,
+            &ResourceCollection::new(5, 0, 0, 0, 0),
+            &ResourceCollection::new(0, 1, 0, 0, 0),
+        ));
+        assert!(!is_valid_maritime_trade(
+            &rates,
+            &ResourceCollection::default(),
+            &ResourceCollection::default(),
+        ));
+    }
+
+    #[test]
+    fn test_trade_book() {
+        let mut book = TradeBook::default();
+        let offer = ResourceCollection::new(1, 0, 0, 0, 0);
+        let request = ResourceCollection::new(0, 1, 0, 0, 0);
+
+        let first = book.post(PlayerColor::Red, None, offer, request, None).id();
+        let second = book
+            .post(PlayerColor::Blue, Some(PlayerColor::Red), request, offer, Some(first))
+            .id();
+        assert_eq!(first, TradeOfferId(0));
+        assert_eq!(second, TradeOfferId(1));
+
+        let open = book.get(first).unwrap();
+        assert!(open.is_open_to(PlayerColor::Blue));
+        assert!(open.is_open_to(PlayerColor::White));
+        assert!(!open.is_open_to(PlayerColor::Red));
+        let counter = book.get(second).unwrap();
+        assert_eq!(counter.counter_to(), Some(first));
+        assert!(!counter.is_open_to(PlayerColor::White));
+
+        book.respond(first, PlayerColor::White, TradeResponse::Reject);
+        let open = book.get(first).unwrap();
+        assert_eq!(open.response(PlayerColor::White), Some(TradeResponse::Reject));
+        assert_eq!(open.response(PlayerColor::Blue), None);
+
+        assert_eq!(book.remove(first).map(|offer| offer.id()), Some(first));
+        assert!(book.get(first).is_none());
+        assert_eq!(book.clear(), vec![second]);
+
+        // IDs keep counting after the book is cleared
+        assert_eq!(book.post(PlayerColor::Red, None, offer, request, None).id(), TradeOfferId(2));
+    }
+}
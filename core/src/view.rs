@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use board::Board;
+use common::pairs;
+use game::{CatanGame, DevelopmentCardType, GameEvent, PlayerColor, ResourceCollection};
+use phase::{ActionKind, GamePhase};
+use trade::TradeOffer;
+
+/// The game as one player is allowed to see it. Opponents' hands are reduced
+/// to card counts, and nothing about the development deck beyond its size or
+/// the seed behind it is included, so a view can be handed to a client or an
+/// agent as is. Events sent alongside it should go through `events_for`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameView {
+    pub viewer: PlayerColor,
+    pub board: Board,
+    pub phase: GamePhase,
+    pub current_player: PlayerColor,
+    pub resource_bank: ResourceCollection,
+    pub development_deck_size: usize,
+    pub longest_road: Option<PlayerColor>,
+    pub largest_army: Option<PlayerColor>,
+    pub trade_offers: Vec<TradeOffer>,
+    pub victory_point_target: u32,
+    /// What the viewer may do right now.
+    pub allowed_actions: Vec<ActionKind>,
+    /// Every player at the table, in seating order.
+    pub players: Vec<PlayerView>,
+    /// The viewer's own cards, or `None` for someone not playing.
+    pub hand: Option<HandView>,
+}
+
+/// What everyone can see about a player.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct PlayerView {
+    pub color: PlayerColor,
+    pub resource_count: u32,
+    pub development_card_count: u32,
+    pub knights_played: u32,
+    /// Excludes victory point cards, which stay hidden until the game ends.
+    pub public_score: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct HandView {
+    pub resources: ResourceCollection,
+    #[serde(with = "pairs")]
+    pub development_cards: HashMap<DevelopmentCardType, u32>,
+}
+
+impl CatanGame {
+    /// The state as seen by `viewer`. A color that isn't seated gets a
+    /// spectator's view with no hand.
+    pub fn view_for(&self, viewer: PlayerColor) -> GameView {
+        let players = self.players()
+            .iter()
+            .map(|player| {
+                PlayerView {
+                    color: player.color(),
+                    resource_count: player.resources().magnitude(),
+                    development_card_count: player.development_card_count(),
+                    knights_played: player.knights_played(),
+                    public_score: self.public_score(player.color()),
+                }
+            })
+            .collect();
+        let hand = self.player(viewer).map(|player| {
+            HandView {
+                resources: *player.resources(),
+                development_cards: player.development_cards().clone(),
+            }
+        });
+
+        GameView {
+            viewer,
+            board: self.board().clone(),
+            phase: self.phase().clone(),
+            current_player: self.current_player(),
+            resource_bank: *self.resource_bank(),
+            development_deck_size: self.development_deck_size(),
+            longest_road: self.longest_road_holder(),
+            largest_army: self.largest_army_holder(),
+            trade_offers: self.trade_offers().to_vec(),
+            victory_point_target: self.settings().victory_point_target,
+            allowed_actions: self.allowed_actions(viewer),
+            players,
+            hand,
+        }
+    }
+}
+
+/// What `viewer` may see of the events an action produced.
+pub fn events_for(events: &[GameEvent], viewer: PlayerColor) -> Vec<GameEvent> {
+    events.iter().map(|event| event.redacted_for(viewer)).collect()
+}
+
+#[cfg(test)]
+mod view_tests {
+    use game::{CatanGame, DevelopmentCardType, DevelopmentVictoryPointType, GameEvent,
+               GameSettings, PlayerColor, ResourceCollection};
+    use game::test_util::{give, give_development_cards};
+    use serde_json;
+    use view::events_for;
+
+    const LIBRARY: DevelopmentCardType =
+        DevelopmentCardType::VictoryPoint(DevelopmentVictoryPointType::Library);
+
+    /// A fresh game where Blue holds five resources, a knight and a library.
+    fn fixture() -> CatanGame {
+        let settings = GameSettings { seed: 5, ..GameSettings::default() };
+        let mut game = CatanGame::with_settings(&[PlayerColor::Red, PlayerColor::Blue], settings)
+            .unwrap();
+
+        give(&mut game, PlayerColor::Blue, ResourceCollection::new(1, 0, 2, 0, 2));
+        give_development_cards(
+            &mut game,
+            PlayerColor::Blue,
+            &[(DevelopmentCardType::Knight, 1), (LIBRARY, 1)],
+        );
+
+        game
+    }
+
+    #[test]
+    fn test_opponent_hand_is_hidden() {
+        let game = fixture();
+        let view = game.view_for(PlayerColor::Red);
+
+        let blue = &view.players[1];
+        assert_eq!(blue.color, PlayerColor::Blue);
+        assert_eq!(blue.resource_count, 5);
+        assert_eq!(blue.development_card_count, 2);
+        assert_eq!(blue.public_score, 0);
+        assert_eq!(game.true_score(PlayerColor::Blue), 1);
+        assert_eq!(view.hand.unwrap().resources, ResourceCollection::default());
+
+        let json = serde_json::to_string(&game.view_for(PlayerColor::Red)).unwrap();
+        assert!(!json.contains("Library"));
+        assert!(!json.contains("seed"));
+        assert!(!json.contains("Knight"));
+    }
+
+    #[test]
+    fn test_own_hand_is_visible() {
+        let view = fixture().view_for(PlayerColor::Blue);
+        let hand = view.hand.unwrap();
+
+        assert_eq!(hand.resources, ResourceCollection::new(1, 0, 2, 0, 2));
+        assert_eq!(hand.development_cards[&LIBRARY], 1);
+        assert_eq!(view.development_deck_size, 25);
+    }
+
+    #[test]
+    fn test_spectator_view() {
+        let game = fixture();
+        let view = game.view_for(PlayerColor::Orange);
+
+        assert!(view.hand.is_none());
+        assert!(view.allowed_actions.is_empty());
+        assert_eq!(view.players.len(), 2);
+        assert_eq!(view.current_player, PlayerColor::Red);
+        assert_eq!(game.view_for(PlayerColor::Red).allowed_actions.len(), 1);
+    }
+
+    #[test]
+    fn test_events_for_opponent() {
+        let events = vec![
+            GameEvent::DiceRolled(3, 4),
+            GameEvent::DevelopmentCardPurchased(PlayerColor::Blue, Some(LIBRARY)),
+        ];
+
+        assert_eq!(events_for(&events, PlayerColor::Blue), events);
+        let seen = events_for(&events, PlayerColor::Red);
+        assert_eq!(seen[0], events[0]);
+        assert_eq!(seen[1], GameEvent::DevelopmentCardPurchased(PlayerColor::Blue, None));
+        assert!(!serde_json::to_string(&seen).unwrap().contains("Library"));
+    }
+}
@@ -8,6 +8,7 @@ use piston_window::context::Context;
 use catan_core::board::{InternalCoord, Board, InternalTileType, ResourceTileType,
                         BuildingTileContainer, HarborType, TILE_COORD_DIR};
 use catan_core::game::PlayerColor;
+use catan_core::geometry::{HexCoord, VertexCoord};
 use catan_core::common::GameResource;
 use colors::*;
 use common::{Renderer, Controller, Builder};
@@ -201,16 +202,18 @@ impl BoardPolygonContainer {
         }
     }
 
-    fn get_distance_polygon(&mut self, distance: f32) -> &Polygon {
-        let rounded_distance = distance.round() as i32;
-        if self.distance_polygons.contains_key(&rounded_distance) {
-            return self.distance_polygons.get(&rounded_distance).unwrap();
+    fn get_distance_polygon(&mut self, distance: u32) -> &Polygon {
+        // Colors were picked for the sum of the coordinate differences, which
+        // is twice the number of lattice steps
+        let color_index = 2 * distance as i32;
+        if self.distance_polygons.contains_key(&color_index) {
+            return self.distance_polygons.get(&color_index).unwrap();
         } else {
-            let new_polygon = Polygon::new(distance_to_color(rounded_distance));
+            let new_polygon = Polygon::new(distance_to_color(color_index));
 
-            self.distance_polygons.insert(rounded_distance, new_polygon);
+            self.distance_polygons.insert(color_index, new_polygon);
 
-            self.distance_polygons.get(&rounded_distance).unwrap()
+            self.distance_polygons.get(&color_index).unwrap()
         }
     }
 }
@@ -373,9 +376,31 @@ where
 }
 
 fn convert_cube_coord_to_cartesian(coord: InternalCoord, size: Scalar) -> Vec2d {
+    match HexCoord::from_internal(coord) {
+        Some(hex) => convert_hex_coord_to_cartesian(hex, size),
+        None => {
+            // A vertex sits at the middle of the three hexes around it
+            let vertex = VertexCoord::from_internal(coord).unwrap();
+            let hexes = vertex.hexes();
+            let centers: Vec<Vec2d> = hexes
+                .iter()
+                .map(|&hex| convert_hex_coord_to_cartesian(hex, size))
+                .collect();
+
+            [
+                centers.iter().map(|center| center[0]).sum::<Scalar>() / hexes.len() as Scalar,
+                centers.iter().map(|center| center[1]).sum::<Scalar>() / hexes.len() as Scalar,
+            ]
+        }
+    }
+}
+
+/// `size` is measured in steps of the board's finer `InternalCoord` lattice,
+/// three of which separate neighboring hexes.
+fn convert_hex_coord_to_cartesian(hex: HexCoord, size: Scalar) -> Vec2d {
     [
-        size * (3.0 as f64).sqrt() * (coord.x as f64 + (coord.y as f64 / 2.0)),
-        size * 1.5 * coord.y as f64,
+        size * 1.5 * (3.0 as f64).sqrt() * (hex.q + hex.r) as f64,
+        size * 1.5 * (hex.r - hex.q) as f64,
     ]
 }
 
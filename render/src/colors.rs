@@ -26,6 +26,8 @@ pub const PLAYER_ORANGE: Color = [255.0 / 255.0, 111.0 / 255.0, 0.0 / 255.0, 1.0
 pub const PLAYER_WHITE: Color = [250.0 / 255.0, 250.0 / 255.0, 250.0 / 255.0, 1.0];
 pub const PLAYER_RED: Color = [183.0 / 255.0, 28.0 / 255.0, 28.0 / 255.0, 1.0];
 pub const PLAYER_BLUE: Color = [26.0 / 255.0, 35.0 / 255.0, 126.0 / 255.0, 1.0];
+pub const PLAYER_GREEN: Color = [27.0 / 255.0, 94.0 / 255.0, 32.0 / 255.0, 1.0];
+pub const PLAYER_BROWN: Color = [93.0 / 255.0, 64.0 / 255.0, 55.0 / 255.0, 1.0];
 
 pub const PERCEPTUAL_RAINBOW: [Color; 16] = [
     [135.0 / 255.0, 59.0 / 255.0, 97.0 / 255.0, 1.0],
@@ -52,6 +54,8 @@ pub fn player_to_color(player: &PlayerColor) -> Color {
         PlayerColor::White => PLAYER_WHITE,
         PlayerColor::Orange => PLAYER_ORANGE,
         PlayerColor::Blue => PLAYER_BLUE,
+        PlayerColor::Green => PLAYER_GREEN,
+        PlayerColor::Brown => PLAYER_BROWN,
     }
 }
 